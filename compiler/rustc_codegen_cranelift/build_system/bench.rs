@@ -5,7 +5,7 @@
 use super::path::{Dirs, RelPath};
 use super::prepare::GitRepo;
 use super::rustc_info::get_file_name;
-use super::utils::{hyperfine_command, spawn_and_wait, CargoProject, Compiler};
+use super::utils::{hyperfine_command, is_ci, spawn_and_wait, CargoProject, Compiler};
 
 static SIMPLE_RAYTRACER_REPO: GitRepo = GitRepo::github(
     "ebobby",
@@ -23,6 +23,7 @@
 
 pub(crate) fn benchmark(dirs: &Dirs, bootstrap_host_compiler: &Compiler) {
     benchmark_simple_raytracer(dirs, bootstrap_host_compiler);
+    benchmark_simd(dirs, bootstrap_host_compiler);
 }
 
 fn benchmark_simple_raytracer(dirs: &Dirs, bootstrap_host_compiler: &Compiler) {
@@ -101,3 +102,52 @@ fn benchmark_simple_raytracer(dirs: &Dirs, bootstrap_host_compiler: &Compiler) {
     bench_run.current_dir(RelPath::BUILD.to_path(dirs));
     spawn_and_wait(bench_run);
 }
+
+/// Compare the per-lane lowering of simd operations against the vector instructions emitted by
+/// LLVM using the workloads in `example/simd-bench.rs`. This uses hyperfine like the other
+/// benchmarks rather than criterion, as the build system can't have any dependencies.
+fn benchmark_simd(dirs: &Dirs, bootstrap_host_compiler: &Compiler) {
+    // This only exists to guide changes to the simd lowering, so there is no need to spend CI time
+    // on it.
+    if is_ci() {
+        eprintln!("[BENCH RUN] simd (skipped on CI)");
+        return;
+    }
+
+    let bench_runs = env::var("BENCH_RUNS").unwrap_or_else(|_| "10".to_string()).parse().unwrap();
+
+    let rustc_clif =
+        RelPath::DIST.to_path(dirs).join(get_file_name("rustc_clif", "bin").replace('_', "-"));
+    for (kind, rustc, out_name) in [
+        ("LLVM BUILD", &bootstrap_host_compiler.rustc, "simd_bench_cg_llvm"),
+        ("BUILD", &rustc_clif, "simd_bench_cg_clif"),
+    ] {
+        eprintln!("[{kind}] simd-bench");
+        let mut build_cmd = std::process::Command::new(rustc);
+        build_cmd
+            .arg("example/simd-bench.rs")
+            .arg("-O")
+            .arg("-o")
+            .arg(RelPath::BUILD.to_path(dirs).join(get_file_name(out_name, "bin")));
+        spawn_and_wait(build_cmd);
+    }
+
+    for workload in ["add", "cmp"] {
+        eprintln!("[BENCH RUN] simd {workload}");
+        let run_cmd = |bin| {
+            format!(
+                "{} {workload} 10000000",
+                Path::new(".").join(get_file_name(bin, "bin")).to_str().unwrap(),
+            )
+        };
+        let mut bench_run = hyperfine_command(
+            1,
+            bench_runs,
+            None,
+            &run_cmd("simd_bench_cg_llvm"),
+            &run_cmd("simd_bench_cg_clif"),
+        );
+        bench_run.current_dir(RelPath::BUILD.to_path(dirs));
+        spawn_and_wait(bench_run);
+    }
+}
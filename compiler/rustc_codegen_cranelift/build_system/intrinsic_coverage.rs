@@ -0,0 +1,44 @@
+use std::collections::BTreeSet;
+use std::fs;
+
+use super::path::{Dirs, RelPath};
+
+static LLVM_INTRINSIC_SOURCES: &[RelPath] = &[
+    RelPath::SOURCE.join("src/intrinsics/llvm.rs"),
+    RelPath::SOURCE.join("src/intrinsics/llvm_aarch64.rs"),
+    RelPath::SOURCE.join("src/intrinsics/llvm_x86.rs"),
+];
+
+/// Print every LLVM intrinsic handled by the match arms of the LLVM intrinsic lowering, one per
+/// line and sorted. Arms matching a whole family using `intrinsic.starts_with(...)` are printed as
+/// the prefix followed by `*`.
+pub(crate) fn run(dirs: &Dirs) {
+    let mut intrinsics = BTreeSet::new();
+    for source in LLVM_INTRINSIC_SOURCES {
+        let source = fs::read_to_string(source.to_path(dirs)).unwrap();
+        collect_handled_intrinsics(&source, &mut intrinsics);
+    }
+
+    for intrinsic in intrinsics {
+        println!("{intrinsic}");
+    }
+}
+
+fn collect_handled_intrinsics(source: &str, intrinsics: &mut BTreeSet<String>) {
+    for line in source.lines() {
+        let line = line.trim();
+        if line.starts_with("\"llvm.") || line.starts_with("| \"llvm.") {
+            // Part of the pattern of a match arm like `"llvm.foo" | "llvm.bar" => {`
+            let pattern = line.split("=>").next().unwrap();
+            for name in pattern.split('|').map(str::trim).filter(|name| !name.is_empty()) {
+                intrinsics.insert(name.trim_matches('"').to_owned());
+            }
+        } else if line.starts_with("_ if intrinsic.starts_with(\"llvm.")
+            || line.starts_with("|| intrinsic.starts_with(\"llvm.")
+        {
+            // Part of the guard of a match arm like `_ if intrinsic.starts_with("llvm.foo.v") => {`
+            let prefix = line.split('"').nth(1).unwrap();
+            intrinsics.insert(format!("{prefix}*"));
+        }
+    }
+}
@@ -9,6 +9,7 @@
 mod build_backend;
 mod build_sysroot;
 mod config;
+mod intrinsic_coverage;
 mod path;
 mod prepare;
 mod rustc_info;
@@ -34,6 +35,7 @@ enum Command {
     Test,
     AbiCafe,
     Bench,
+    IntrinsicCoverage,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -66,6 +68,7 @@ pub(crate) fn main() {
         Some("test") => Command::Test,
         Some("abi-cafe") => Command::AbiCafe,
         Some("bench") => Command::Bench,
+        Some("intrinsic-coverage") => Command::IntrinsicCoverage,
         Some(flag) if flag.starts_with('-') => arg_error!("Expected command found flag {}", flag),
         Some(command) => arg_error!("Unknown command {}", command),
         None => {
@@ -138,6 +141,11 @@ pub(crate) fn main() {
         process::exit(0);
     }
 
+    if command == Command::IntrinsicCoverage {
+        intrinsic_coverage::run(&dirs);
+        process::exit(0);
+    }
+
     env::set_var("RUSTC", "rustc_should_be_set_explicitly");
     env::set_var("RUSTDOC", "rustdoc_should_be_set_explicitly");
 
@@ -148,7 +156,7 @@ pub(crate) fn main() {
         use_unstable_features,
     );
     match command {
-        Command::Prepare => {
+        Command::Prepare | Command::IntrinsicCoverage => {
             // Handled above
         }
         Command::Test => {
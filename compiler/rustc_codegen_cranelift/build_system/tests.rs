@@ -20,7 +20,9 @@ struct TestCase {
 enum TestCaseCmd {
     Custom { func: &'static dyn Fn(&TestRunner) },
     BuildLib { source: &'static str, crate_types: &'static str },
-    BuildBinAndRun { source: &'static str, args: &'static [&'static str] },
+    BuildBinAndRun { source: &'static str, rustflags: &'static str, args: &'static [&'static str] },
+    BuildFail { source: &'static str, expected_error: &'static str },
+    BuildFailWithStderr { source: &'static str },
     JitBin { source: &'static str, args: &'static str },
 }
 
@@ -43,7 +45,29 @@ const fn build_bin_and_run(
         source: &'static str,
         args: &'static [&'static str],
     ) -> Self {
-        Self { config, cmd: TestCaseCmd::BuildBinAndRun { source, args } }
+        Self { config, cmd: TestCaseCmd::BuildBinAndRun { source, rustflags: "", args } }
+    }
+
+    const fn build_bin_and_run_with_rustflags(
+        config: &'static str,
+        source: &'static str,
+        rustflags: &'static str,
+        args: &'static [&'static str],
+    ) -> Self {
+        Self { config, cmd: TestCaseCmd::BuildBinAndRun { source, rustflags, args } }
+    }
+
+    const fn build_fail(
+        config: &'static str,
+        source: &'static str,
+        expected_error: &'static str,
+    ) -> Self {
+        Self { config, cmd: TestCaseCmd::BuildFail { source, expected_error } }
+    }
+
+    /// Like `build_fail`, but compares the full stderr against the `.stderr` file next to `source`.
+    const fn build_fail_with_stderr(config: &'static str, source: &'static str) -> Self {
+        Self { config, cmd: TestCaseCmd::BuildFailWithStderr { source } }
     }
 
     const fn jit_bin(config: &'static str, source: &'static str, args: &'static str) -> Self {
@@ -90,7 +114,100 @@ const fn jit_bin(config: &'static str, source: &'static str, args: &'static str)
     ),
     TestCase::build_bin_and_run("aot.float-minmax-pass", "example/float-minmax-pass.rs", &[]),
     TestCase::build_bin_and_run("aot.mod_bench", "example/mod_bench.rs", &[]),
+    TestCase::build_bin_and_run("aot.simd-bench", "example/simd-bench.rs", &[]),
     TestCase::build_bin_and_run("aot.issue-72793", "example/issue-72793.rs", &[]),
+    TestCase::build_bin_and_run("aot.intrinsic-semantics", "tests/intrinsic_semantics.rs", &[]),
+    TestCase::build_bin_and_run(
+        "aot.target-feature-dispatch",
+        "example/target-feature-dispatch.rs",
+        &[],
+    ),
+    TestCase::build_bin_and_run_with_rustflags(
+        "aot.target-feature-dispatch-native",
+        "example/target-feature-dispatch.rs",
+        "-Ctarget-feature=+sse4.2,+pclmulqdq",
+        &[],
+    ),
+    TestCase::build_lib("build.x86-monitor-mwait", "example/x86-monitor-mwait.rs", "lib"),
+    TestCase::build_bin_and_run("aot.x86-rdpid-rdpmc", "example/x86-rdpid-rdpmc.rs", &[]),
+    TestCase::build_bin_and_run("aot.x86-cache-writeback", "example/x86-cache-writeback.rs", &[]),
+    TestCase::build_bin_and_run("aot.x86-cvtpi2pd", "example/x86-cvtpi2pd.rs", &[]),
+    TestCase::custom("build.dump-clif-fn", &|runner| {
+        let mut cmd = runner.rustc_command(["example/dump-clif-fn.rs", "--crate-type", "lib"]);
+        cmd.env("CG_CLIF_DUMP_FN", "dump_clif_fn::add_one");
+        let output = cmd.output().unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success()
+            || !stderr.contains("add_one")
+            || !stderr.contains("function ")
+            || stderr.contains("not_dumped")
+        {
+            eprintln!("{stderr}");
+            eprintln!("Expected only the clif ir of `dump_clif_fn::add_one` to be printed");
+            std::process::exit(1);
+        }
+    }),
+    TestCase::build_bin_and_run("aot.simd-lanewise-binop", "example/simd-lanewise-binop.rs", &[]),
+    TestCase::custom("build.simd-lanewise-binop", &|runner| {
+        if !["x86_64", "aarch64", "s390x"]
+            .iter()
+            .any(|arch| runner.target_compiler.triple.starts_with(arch))
+        {
+            eprintln!("Skipping: no vector support in Cranelift for this target");
+            return;
+        }
+
+        let mut cmd =
+            runner.rustc_command(["example/simd-lanewise-binop.rs", "--crate-type", "lib"]);
+        cmd.env("CG_CLIF_DUMP_FN", "simd_lanewise_binop::add_f32x4");
+        let output = cmd.output().unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() || !stderr.contains("load.f32x4") || !stderr.contains("fadd") {
+            eprintln!("{stderr}");
+            eprintln!("Expected `simd_lanewise_binop::add_f32x4` to use a f32x4 fadd");
+            std::process::exit(1);
+        }
+    }),
+    TestCase::custom("aot.unsupported-llvm-intrinsic", &|runner| {
+        runner.run_rustc(["example/unsupported-llvm-intrinsic.rs"]);
+        let output = runner.out_command("unsupported-llvm-intrinsic", &[]).output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !stdout.contains("unsupported intrinsic `llvm.cg-clif.unsupported-for-test`") {
+            eprintln!("{stdout}");
+            eprintln!("Expected the trap to print the name of the unsupported intrinsic");
+            std::process::exit(1);
+        }
+        // The message has to be printed before the process is killed by the trap rather than
+        // exiting normally
+        #[cfg(unix)]
+        let trapped = std::os::unix::process::ExitStatusExt::signal(&output.status).is_some();
+        #[cfg(not(unix))]
+        let trapped = !output.status.success();
+        if !trapped {
+            eprintln!("{stdout}");
+            eprintln!("Expected the unsupported intrinsic to trap, got {}", output.status);
+            std::process::exit(1);
+        }
+    }),
+    TestCase::build_fail_with_stderr(
+        "build_fail.simd-extract-out-of-bounds",
+        "example/simd-extract-out-of-bounds.rs",
+    ),
+    TestCase::build_fail(
+        "build_fail.llvm-intrinsic-non-const-imm",
+        "example/llvm-intrinsic-non-const-imm.rs",
+        "expected a constant argument to llvm.x86.sse2.psrli.d",
+    ),
+    TestCase::build_fail(
+        "build_fail.llvm-intrinsic-malformed",
+        "example/llvm-intrinsic-malformed.rs",
+        "invalid call to `llvm.x86.addcarry.64`: expected operand 1 to be of type `u64`, found `u32`",
+    ),
+    TestCase::build_fail(
+        "build_fail.llvm-intrinsic-ptrmask-width",
+        "example/llvm-intrinsic-ptrmask-width.rs",
+        "invalid call to `llvm.ptrmask.p0.i32`: expected the pointer and the mask to be",
+    ),
 ];
 
 // FIXME(rust-random/rand#1293): Newer rand versions fail to test on Windows. Update once this is
@@ -322,13 +439,24 @@ fn run_testsuite(&self, tests: &[TestCase]) {
                 TestCaseCmd::BuildLib { source, crate_types } => {
                     self.run_rustc([source, "--crate-type", crate_types]);
                 }
-                TestCaseCmd::BuildBinAndRun { source, args } => {
-                    self.run_rustc([source]);
+                TestCaseCmd::BuildBinAndRun { source, rustflags, args } => {
+                    self.run_rustc([source].into_iter().chain(rustflags.split_whitespace()));
                     self.run_out_command(
                         source.split('/').last().unwrap().split('.').next().unwrap(),
                         args,
                     );
                 }
+                TestCaseCmd::BuildFail { source, expected_error } => {
+                    self.run_rustc_expecting_error([source, "--crate-type", "lib"], expected_error);
+                }
+                TestCaseCmd::BuildFailWithStderr { source } => {
+                    let expected_stderr =
+                        fs::read_to_string(Path::new(source).with_extension("stderr")).unwrap();
+                    self.run_rustc_expecting_stderr(
+                        [source, "--crate-type", "lib"],
+                        &expected_stderr,
+                    );
+                }
                 TestCaseCmd::JitBin { source, args } => {
                     let mut jit_cmd = self.rustc_command([
                         "-Zunstable-options",
@@ -389,7 +517,41 @@ fn run_rustc<I, S>(&self, args: I)
         spawn_and_wait(self.rustc_command(args));
     }
 
+    fn run_rustc_expecting_error<I, S>(&self, args: I, expected_error: &str)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let output = self.rustc_command(args).output().unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.success() || !stderr.contains(expected_error) {
+            eprintln!("{stderr}");
+            eprintln!("Expected compilation to fail with `{expected_error}`");
+            std::process::exit(1);
+        }
+    }
+
+    fn run_rustc_expecting_stderr<I, S>(&self, args: I, expected_stderr: &str)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let output = self.rustc_command(args).output().unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.success() || stderr != expected_stderr {
+            eprintln!("{stderr}");
+            eprintln!("Expected compilation to fail with the following stderr:");
+            eprintln!("{expected_stderr}");
+            std::process::exit(1);
+        }
+    }
+
     fn run_out_command(&self, name: &str, args: &[&str]) {
+        spawn_and_wait(self.out_command(name, args));
+    }
+
+    #[must_use]
+    fn out_command(&self, name: &str, args: &[&str]) -> Command {
         let mut full_cmd = vec![];
 
         // Prepend the RUN_WRAPPER's
@@ -410,7 +572,6 @@ fn run_out_command(&self, name: &str, args: &[&str]) {
 
         let mut cmd = Command::new(first);
         cmd.args(cmd_iter);
-
-        spawn_and_wait(cmd);
+        cmd
     }
 }
@@ -0,0 +1,10 @@
+// Used by the build.dump-clif-fn test to check that CG_CLIF_DUMP_FN prints the clif ir of the
+// requested function.
+
+pub fn add_one(x: u32) -> u32 {
+    x.wrapping_add(1)
+}
+
+pub fn not_dumped(x: u32) -> u32 {
+    x.wrapping_sub(1)
+}
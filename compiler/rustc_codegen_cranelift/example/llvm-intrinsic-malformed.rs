@@ -0,0 +1,14 @@
+// Test that calling an llvm intrinsic with the wrong operand types is reported as an error rather
+// than causing an ICE.
+
+#![feature(link_llvm_intrinsics)]
+#![allow(improper_ctypes)]
+
+extern "C" {
+    #[link_name = "llvm.x86.addcarry.64"]
+    fn addcarry64(c_in: u8, a: u32, b: u32) -> (u8, u64);
+}
+
+pub fn addcarry_wrong_operand_type(a: u32, b: u32) -> (u8, u64) {
+    unsafe { addcarry64(0, a, b) }
+}
@@ -0,0 +1,18 @@
+// Test that passing a non-const immediate to an llvm intrinsic is reported as an error rather
+// than causing an ICE.
+
+#![feature(repr_simd, simd_ffi, link_llvm_intrinsics)]
+#![allow(non_camel_case_types, improper_ctypes)]
+
+#[repr(simd)]
+#[derive(Copy, Clone)]
+pub struct i32x4(i32, i32, i32, i32);
+
+extern "C" {
+    #[link_name = "llvm.x86.sse2.psrli.d"]
+    fn psrlid(a: i32x4, imm8: i32) -> i32x4;
+}
+
+pub fn psrli_non_const_imm(a: i32x4, imm8: i32) -> i32x4 {
+    unsafe { psrlid(a, imm8) }
+}
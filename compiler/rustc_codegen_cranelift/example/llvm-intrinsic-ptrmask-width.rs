@@ -0,0 +1,13 @@
+// Test that calling llvm.ptrmask with a mask that doesn't have the width of a pointer is reported as
+// an error rather than causing an ICE.
+
+#![feature(link_llvm_intrinsics)]
+
+extern "C" {
+    #[link_name = "llvm.ptrmask.p0.i32"]
+    fn ptrmask(ptr: *const u8, mask: u32) -> *const u8;
+}
+
+pub fn ptrmask_narrow_mask(ptr: *const u8) -> *const u8 {
+    unsafe { ptrmask(ptr, !15) }
+}
@@ -0,0 +1,65 @@
+// Workloads used by `./y.rs bench` to compare the per-lane simd lowering of cg_clif against the
+// vector instructions emitted by LLVM. Run as `simd-bench [add|cmp|all] [iterations]`. The
+// defaults are small enough for this to double as a regular test.
+
+#![feature(repr_simd, platform_intrinsics)]
+#![allow(non_camel_case_types)]
+
+use std::hint::black_box;
+
+#[repr(simd)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct f32x8(f32, f32, f32, f32, f32, f32, f32, f32);
+
+#[repr(simd)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct i32x8(i32, i32, i32, i32, i32, i32, i32, i32);
+
+extern "platform-intrinsic" {
+    fn simd_add<T>(x: T, y: T) -> T;
+    fn simd_lt<T, U>(x: T, y: T) -> U;
+    fn simd_sub<T>(x: T, y: T) -> T;
+}
+
+fn add_loop(iterations: u64) -> f32x8 {
+    let step = black_box(f32x8(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0));
+    let mut acc = f32x8(0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+    for _ in 0..iterations {
+        acc = unsafe { simd_add(black_box(acc), step) };
+    }
+    acc
+}
+
+fn cmp_loop(iterations: u64) -> i32x8 {
+    let a = black_box(f32x8(1.0, 8.0, 3.0, 6.0, 5.0, 4.0, 7.0, 2.0));
+    let b = black_box(f32x8(4.5, 4.5, 4.5, 4.5, 4.5, 4.5, 4.5, 4.5));
+    let mut count = i32x8(0, 0, 0, 0, 0, 0, 0, 0);
+    for _ in 0..iterations {
+        // The comparison yields -1 for true lanes, so subtracting counts the matches
+        let mask: i32x8 = unsafe { simd_lt(black_box(a), b) };
+        count = unsafe { simd_sub(black_box(count), mask) };
+    }
+    count
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let workload = args.next().unwrap_or_else(|| "all".to_string());
+    let iterations = args.next().map_or(1000, |arg| arg.parse().unwrap());
+
+    if workload == "add" || workload == "all" {
+        let acc = add_loop(iterations);
+        // Above this the sums are no longer exactly representable as f32
+        if iterations <= 1 << 20 {
+            let n = iterations as f32;
+            let expected = f32x8(n, 2.0 * n, 3.0 * n, 4.0 * n, 5.0 * n, 6.0 * n, 7.0 * n, 8.0 * n);
+            assert_eq!(acc, expected);
+        }
+    }
+
+    if workload == "cmp" || workload == "all" {
+        let count = cmp_loop(iterations);
+        let n = iterations as i32;
+        assert_eq!(count, i32x8(n, 0, n, 0, 0, n, 0, n));
+    }
+}
@@ -0,0 +1,30 @@
+// Test that an out of bounds or non-constant lane index passed to `simd_extract` and `simd_insert`
+// is reported as an error rather than causing an ICE or silently accessing the wrong lane.
+
+#![feature(repr_simd, platform_intrinsics)]
+#![allow(non_camel_case_types)]
+
+#[repr(simd)]
+#[derive(Copy, Clone)]
+pub struct i32x4(i32, i32, i32, i32);
+
+extern "platform-intrinsic" {
+    fn simd_extract<T, U>(x: T, idx: u32) -> U;
+    fn simd_insert<T, U>(x: T, idx: u32, val: U) -> T;
+}
+
+pub fn extract_out_of_bounds(a: i32x4) -> i32 {
+    unsafe { simd_extract(a, 4) }
+}
+
+pub fn insert_out_of_bounds(a: i32x4) -> i32x4 {
+    unsafe { simd_insert(a, 4, 0i32) }
+}
+
+pub fn extract_non_const(a: i32x4, idx: u32) -> i32 {
+    unsafe { simd_extract(a, idx) }
+}
+
+pub fn insert_non_const(a: i32x4, idx: u32) -> i32x4 {
+    unsafe { simd_insert(a, idx, 0i32) }
+}
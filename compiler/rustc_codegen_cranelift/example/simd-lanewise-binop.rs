@@ -0,0 +1,52 @@
+// Check that lanewise simd operations give the same results whether they are lowered to a single
+// vector instruction or applied lane by lane. The build.simd-lanewise-binop test additionally
+// checks that `add_f32x4` actually uses a vector instruction.
+
+#![feature(repr_simd, platform_intrinsics)]
+#![allow(non_camel_case_types)]
+
+#[repr(simd)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct f32x4(f32, f32, f32, f32);
+
+// 256bit vectors aren't supported by Cranelift, so these are always applied lane by lane
+#[repr(simd)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct f32x8(f32, f32, f32, f32, f32, f32, f32, f32);
+
+#[repr(simd)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct i32x4(i32, i32, i32, i32);
+
+extern "platform-intrinsic" {
+    fn simd_add<T>(x: T, y: T) -> T;
+    fn simd_sub<T>(x: T, y: T) -> T;
+    fn simd_xor<T>(x: T, y: T) -> T;
+}
+
+pub fn add_f32x4(x: f32x4, y: f32x4) -> f32x4 {
+    unsafe { simd_add(x, y) }
+}
+
+fn add_f32x8(x: f32x8, y: f32x8) -> f32x8 {
+    unsafe { simd_add(x, y) }
+}
+
+fn main() {
+    let x = f32x4(1.5, -0.0, f32::INFINITY, 1e30);
+    let y = f32x4(2.25, 0.0, 1.0, 1e30);
+    let res = add_f32x4(std::hint::black_box(x), std::hint::black_box(y));
+    let expected = add_f32x8(
+        f32x8(x.0, x.1, x.2, x.3, 0.0, 0.0, 0.0, 0.0),
+        f32x8(y.0, y.1, y.2, y.3, 0.0, 0.0, 0.0, 0.0),
+    );
+    assert_eq!(res, f32x4(expected.0, expected.1, expected.2, expected.3));
+    assert_eq!(res, f32x4(3.75, 0.0, f32::INFINITY, 2e30));
+    assert!(res.1.is_sign_positive());
+
+    let a = std::hint::black_box(i32x4(i32::MAX, -1, 7, 0));
+    let b = std::hint::black_box(i32x4(1, 1, -8, 0x5555));
+    assert_eq!(unsafe { simd_add(a, b) }, i32x4(i32::MIN, 0, -1, 0x5555));
+    assert_eq!(unsafe { simd_sub(a, b) }, i32x4(i32::MAX - 1, -2, 15, -0x5555));
+    assert_eq!(unsafe { simd_xor(a, b) }, i32x4(i32::MAX ^ 1, -2, 7 ^ -8, 0x5555));
+}
@@ -1,4 +1,16 @@
-#![feature(core_intrinsics, generators, generator_trait, is_sorted, repr_simd)]
+#![feature(
+    core_intrinsics,
+    generators,
+    generator_trait,
+    is_sorted,
+    link_llvm_intrinsics,
+    ptr_mask,
+    avx512_target_feature,
+    platform_intrinsics,
+    repr_simd,
+    simd_ffi,
+    stdsimd
+)]
 
 #[cfg(target_arch = "x86_64")]
 use std::arch::x86_64::*;
@@ -101,6 +113,9 @@ fn main() {
     }
 
     test_checked_mul();
+    test_llvm_bswap();
+    test_ptr_mask();
+    test_contended_atomics();
 
     let _a = 1u32 << 2u8;
 
@@ -155,6 +170,10 @@ enum Never {}
     }
 
     foo(I64X2(0, 0));
+
+    test_simd_expose_addr();
+    test_simd_reduce_all_any();
+    test_simd_reduce_min_max_nan();
 }
 
 fn panic(_: u128) {
@@ -167,6 +186,82 @@ fn panic(_: u128) {
 #[allow(improper_ctypes_definitions)]
 extern "C" fn foo(_a: I64X2) {}
 
+#[repr(simd)]
+#[derive(Copy, Clone)]
+struct PtrX2(*const u8, *const u8);
+
+#[repr(simd)]
+#[derive(Copy, Clone)]
+struct UsizeX2(usize, usize);
+
+extern "platform-intrinsic" {
+    fn simd_expose_addr<T, U>(ptr: T) -> U;
+    fn simd_from_exposed_addr<T, U>(addr: T) -> U;
+    fn simd_reduce_all<T>(x: T) -> bool;
+    fn simd_reduce_any<T>(x: T) -> bool;
+    fn simd_reduce_min<T, U>(x: T) -> U;
+    fn simd_reduce_max<T, U>(x: T) -> U;
+}
+
+fn test_simd_expose_addr() {
+    let bytes = [1u8, 2, 3, 4];
+    let ptrs = PtrX2(&bytes[1], &bytes[3]);
+
+    let addrs: UsizeX2 = unsafe { simd_expose_addr(ptrs) };
+    assert_eq!((addrs.0, addrs.1), (ptrs.0 as usize, ptrs.1 as usize));
+
+    let ptrs: PtrX2 = unsafe { simd_from_exposed_addr(addrs) };
+    assert_eq!(unsafe { (*ptrs.0, *ptrs.1) }, (2, 4));
+}
+
+#[repr(simd)]
+#[derive(Copy, Clone)]
+struct MaskX5(i32, i32, i32, i32, i32);
+
+fn test_simd_reduce_all_any() {
+    // An odd lane count to exercise the unpaired lane of the reduction tree
+    let all_true = MaskX5(-1, -1, -1, -1, -1);
+    let all_false = MaskX5(0, 0, 0, 0, 0);
+    let all_any = |m: MaskX5| unsafe { (simd_reduce_all(m), simd_reduce_any(m)) };
+
+    assert_eq!(all_any(all_true), (true, true));
+    assert_eq!(all_any(all_false), (false, false));
+    for lane in 0..5 {
+        let mut one_false = [-1; 5];
+        one_false[lane] = 0;
+        let [a, b, c, d, e] = one_false;
+        assert_eq!(all_any(MaskX5(a, b, c, d, e)), (false, true), "lane {lane}");
+
+        let mut one_true = [0; 5];
+        one_true[lane] = -1;
+        let [a, b, c, d, e] = one_true;
+        assert_eq!(all_any(MaskX5(a, b, c, d, e)), (false, true), "lane {lane}");
+    }
+}
+
+#[repr(simd)]
+#[derive(Copy, Clone)]
+struct F32X4(f32, f32, f32, f32);
+
+fn test_simd_reduce_min_max_nan() {
+    // NaN lanes are ignored as long as there is any other lane, no matter where they are
+    let min_max =
+        |v: F32X4| unsafe { (simd_reduce_min::<_, f32>(v), simd_reduce_max::<_, f32>(v)) };
+    let nan = f32::NAN;
+
+    assert_eq!(min_max(F32X4(nan, 3.0, -1.0, 2.0)), (-1.0, 3.0));
+    assert_eq!(min_max(F32X4(3.0, nan, nan, 2.0)), (2.0, 3.0));
+    assert_eq!(min_max(F32X4(-1.0, 4.0, 2.0, nan)), (-1.0, 4.0));
+    assert_eq!(min_max(F32X4(nan, nan, nan, 5.0)), (5.0, 5.0));
+    assert_eq!(
+        min_max(F32X4(f32::NEG_INFINITY, nan, f32::INFINITY, nan)),
+        (f32::NEG_INFINITY, f32::INFINITY)
+    );
+
+    let (min, max) = min_max(F32X4(nan, nan, nan, nan));
+    assert!(min.is_nan() && max.is_nan());
+}
+
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "sse2")]
 unsafe fn test_simd() {
@@ -188,6 +283,8 @@ unsafe fn test_simd() {
     assert_eq!(std::mem::transmute::<_, [u16; 8]>(cmp_lt), [0, 0, 0, 0, 0, 0, 0, 0]);
 
     test_mm_slli_si128();
+    test_mm_slli_si128_zero_fill();
+    test_mm_srli_si128();
     test_mm_movemask_epi8();
     test_mm256_movemask_epi8();
     test_mm_add_epi8();
@@ -197,6 +294,153 @@ unsafe fn test_simd() {
 
     test_mm_extract_epi8();
     test_mm_insert_epi16();
+    test_mm_mul_epu32();
+    test_mm256_mul_epu32();
+    test_mm_mul_epi32();
+    test_mm256_mul_epi32();
+    test_mm_extract_epi16();
+    test_mm_extract_epi32();
+    test_mm_extract_epi64();
+    test_mm_insert_epi8();
+    test_mm_insert_epi32();
+    test_mm_insert_epi64();
+    test_mm_cmpeq_ps();
+    test_mm_cmp_ps_avx_predicates();
+    test_mm_maskmoveu_si128();
+    test_mm_maskmoveu_si128_subslice();
+    #[cfg(target_os = "linux")]
+    test_mm_maskmoveu_si128_page_boundary();
+    test_mm256_permutevar8x32_epi32();
+    test_mm256_permutevar8x32_ps();
+    test_mm256_permute4x64_epi64();
+    test_mm256_permute4x64_pd();
+    test_mm_permute_ps();
+    test_mm256_permute_pd();
+    test_mm_permutevar_ps();
+    test_mm256_permutevar_ps();
+    test_mm_permutevar_pd();
+    test_mm256_permutevar_pd();
+    test_mm_unpack_epi8();
+    test_mm_unpack_epi64();
+    test_mm_cmp_ss();
+    test_mm_cmp_sd();
+    test_mm_cmp_pd_avx_predicates();
+    test_mm_cmp_ss_sd_avx_predicates();
+    test_kand_mask16();
+    test_kor_mask16();
+    test_kxor_mask16();
+    test_knot_mask16();
+    test_mm_sign_epi8();
+    test_mm_sign_epi16();
+    test_mm_sign_epi32();
+    test_mm256_sign_epi16();
+    test_mm256_sign_epi8_epi32();
+    test_mm_abs_epi8();
+    test_mm_abs_epi16();
+    test_mm_abs_epi32();
+    test_mm256_abs_epi32();
+    test_mm256_abs_epi8_epi16();
+    test_mm_alignr_epi8();
+    test_mm_minpos_epu16();
+    test_mm_blend_ps();
+    test_mm_blend_pd();
+    test_mm_blend_epi16();
+    test_mm_blendv_pd_sign_bit();
+    test_mm_blendv_ps_epi8();
+    test_mm_cvtepi32_ps();
+    test_mm_cvtepi32_pd();
+    test_mm_cvtps_epi32();
+    test_mm_cvtpd_epi32();
+    test_mm_cvtpd_epi32_halfway();
+    test_mm_cvttpd_epi32();
+    test_mm256_cvtepi32_ps();
+    test_mm256_cvtps_epi32();
+    test_mm256_cvtpd_epi32();
+    test_mm_max_epi16();
+    test_mm_min_epi16();
+    test_mm_max_epu8();
+    test_mm_min_epu8();
+    test_mm_max_ps();
+    test_mm_min_ps();
+    test_mm_max_ss();
+    test_mm_min_sd();
+    test_mm256_max_pd();
+    test_mm_andnot_ps();
+    test_mm_andnot_pd();
+    test_mm_movehdup_ps();
+    test_mm_moveldup_ps();
+    test_mm_move_epi64();
+    test_mm_cvtsi32_si128();
+    test_mm_mpsadbw_epu8();
+    test_mm_mpsadbw_epu8_max_sums();
+    test_mm_packus_epi32();
+    test_mm_packs_epi16_epi32();
+    test_mm256_packs_packus();
+    test_pmovmskb_u32_result();
+    test_mm_hadd_ps();
+    test_mm256_hadd_ps();
+    test_mm256_hsub_pd();
+    test_mm_hadd_hsub_lane_order();
+    test_mm_rcp_rsqrt_ps_ss();
+    test_mm256_rcp_rsqrt_ps();
+    test_mm_cvtps_pd();
+    test_mm_sqrt_pd();
+    test_mm_dp_ps();
+    test_mm_dp_ps_nan();
+    test_mm_dp_ps_accumulation_order();
+    test_mm256_dp_ps_per_half();
+    test_mm_dp_pd();
+    test_mm_mullo_mulhi_epi16();
+    test_mm_mullo_epi32();
+    test_movemask_epi8_lane_order();
+    test_mm_movemask_pd_two_lanes();
+    test_mm256_movemask_epi8_all_bits();
+    test_mm_testz_si128();
+    test_mm256_testz_si256();
+    test_mm256_testz_ps_pd();
+    test_mm256_blend_epi32();
+    test_mm_cvtsd_si64();
+    test_mm_cvtss_si32_si64();
+    test_mm_cvtss_si32_ties_to_even();
+    test_mm_cvtsi64_ss_sd();
+    test_mm_cvtsi64_ss_rounding();
+    test_mm_cvtsi_sd_ss_upper_nan_bits();
+    test_mm_subs_epu8_epu16();
+    test_mm_extract_ps();
+    test_mm_movemask_ps();
+    test_mm256_movemask_ps_pd();
+    test_mm_comi_ucomi_ss();
+    test_mm_comi_ucomi_sd();
+    test_mm_mulhrs_epi16();
+    test_mm256_mulhrs_epi16();
+    test_mm_madd_epi16();
+    test_mm_madd_epi16_sign_extension();
+    test_mm256_maddubs_epi16();
+    test_mm_round_ps();
+    test_mm_round_ss_sd();
+    test_mm_srai_epi16();
+    test_mm_srai_epi32();
+    test_mm_sra_epi16();
+    test_mm_sra_epi32();
+    test_mm_sllv_srlv_epi64();
+    test_mm256_sllv_srlv_srav_epi32();
+    test_mm256_mpsadbw_epu8();
+    test_mm_sad_epu8_result_placement();
+    test_mm256_sad_epu8();
+    test_mm512_mask_add_sub_mullo_epi32();
+    test_mm512_kortestc();
+    test_mm512_compress_expand_epi32();
+    test_mm256_compress_expand_ps_pd();
+    test_mm512_compress_expand_epi8_epi16();
+    test_mm512_mask_cvtps_epi32();
+    test_mm512_mask_cvtpd_cvttps_epi32();
+    test_andn_u32();
+    test_andn_u64();
+    test_bls_u32();
+    test_bls_u64();
+    test_xsave_xrstor();
+    test_fxsave_fxrstor();
+    test_mm_getcsr_setcsr();
 
     #[rustfmt::skip]
     let mask1 = _mm_movemask_epi8(dbg!(_mm_setr_epi8(255u8 as i8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0)));
@@ -230,6 +474,36 @@ unsafe fn test_mm_slli_si128() {
     assert_eq_m128i(r, _mm_set1_epi8(0));
 }
 
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_slli_si128_zero_fill() {
+    // The vacated low bytes must be zero rather than whatever was shifted out of the register
+    let r = std::mem::transmute::<_, [u8; 16]>(_mm_slli_si128(_mm_set1_epi8(-1), 4));
+    assert_eq!(r[..4], [0; 4]);
+    assert_eq!(r[4..], [0xff; 12]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_srli_si128() {
+    #[rustfmt::skip]
+    let a = _mm_setr_epi8(
+        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+    );
+    let r = _mm_srli_si128(a, 1);
+    let e = _mm_setr_epi8(2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 0);
+    assert_eq_m128i(r, e);
+
+    let r = _mm_srli_si128(a, 15);
+    let e = _mm_setr_epi8(16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+    assert_eq_m128i(r, e);
+
+    // Any count of 16 or more clears the whole register
+    assert_eq_m128i(_mm_srli_si128(a, 16), _mm_set1_epi8(0));
+    assert_eq_m128i(_mm_srli_si128(a, 255), _mm_set1_epi8(0));
+    assert_eq_m128i(_mm_slli_si128(a, 255), _mm_set1_epi8(0));
+}
+
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "sse2")]
 unsafe fn test_mm_movemask_epi8() {
@@ -285,6 +559,13 @@ fn assert_eq_m128i(x: std::arch::x86_64::__m128i, y: std::arch::x86_64::__m128i)
     }
 }
 
+#[cfg(target_arch = "x86_64")]
+fn assert_eq_m256i(x: std::arch::x86_64::__m256i, y: std::arch::x86_64::__m256i) {
+    unsafe {
+        assert_eq!(std::mem::transmute::<_, [u8; 32]>(x), std::mem::transmute::<_, [u8; 32]>(y));
+    }
+}
+
 #[cfg(target_arch = "x86_64")]
 #[target_feature(enable = "sse2")]
 pub unsafe fn assert_eq_m128d(a: __m128d, b: __m128d) {
@@ -323,8 +604,12 @@ unsafe fn test_mm_extract_epi8() {
     );
     let r1 = _mm_extract_epi8(a, 0);
     let r2 = _mm_extract_epi8(a, 3);
+    let r3 = _mm_extract_epi8(a, 9);
+    let r4 = _mm_extract_epi8(a, 15);
     assert_eq!(r1, 0xFF);
     assert_eq!(r2, 3);
+    assert_eq!(r3, 9);
+    assert_eq!(r4, 15);
 }
 
 #[cfg(target_arch = "x86_64")]
@@ -336,25 +621,2276 @@ unsafe fn test_mm_insert_epi16() {
     assert_eq_m128i(r, e);
 }
 
-fn test_checked_mul() {
-    let u: Option<u8> = u8::from_str_radix("1000", 10).ok();
-    assert_eq!(u, None);
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_mul_epu32() {
+    // The odd lanes must be ignored
+    let a = _mm_setr_epi32(0xFFFF_FFFFu32 as i32, 7, 3, 9);
+    let b = _mm_setr_epi32(0x1_0001, 11, 0x8000_0000u32 as i32, 13);
+    let r = _mm_mul_epu32(a, b);
+    let e = _mm_set_epi64x(0x8000_0000 * 3, 0xFFFF_FFFF * 0x1_0001);
+    assert_eq_m128i(r, e);
+}
 
-    assert_eq!(1u8.checked_mul(255u8), Some(255u8));
-    assert_eq!(255u8.checked_mul(255u8), None);
-    assert_eq!(1i8.checked_mul(127i8), Some(127i8));
-    assert_eq!(127i8.checked_mul(127i8), None);
-    assert_eq!((-1i8).checked_mul(-127i8), Some(127i8));
-    assert_eq!(1i8.checked_mul(-128i8), Some(-128i8));
-    assert_eq!((-128i8).checked_mul(-128i8), None);
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_mul_epu32() {
+    let a = _mm256_setr_epi32(0xFFFF_FFFFu32 as i32, 1, 2, 3, 4, 5, 0x1234_5678, 7);
+    let b = _mm256_setr_epi32(0xFFFF_FFFFu32 as i32, 1, 10, 3, 0x4000_0000, 5, 0x10, 7);
+    let r = _mm256_mul_epu32(a, b);
+    let e = _mm256_setr_epi64x(0xFFFF_FFFE_0000_0001u64 as i64, 20, 0x1_0000_0000, 0x1_2345_6780);
+    assert_eq_m256i(r, e);
+}
 
-    assert_eq!(1u64.checked_mul(u64::MAX), Some(u64::MAX));
-    assert_eq!(u64::MAX.checked_mul(u64::MAX), None);
-    assert_eq!(1i64.checked_mul(i64::MAX), Some(i64::MAX));
-    assert_eq!(i64::MAX.checked_mul(i64::MAX), None);
-    assert_eq!((-1i64).checked_mul(i64::MIN + 1), Some(i64::MAX));
-    assert_eq!(1i64.checked_mul(i64::MIN), Some(i64::MIN));
-    assert_eq!(i64::MIN.checked_mul(i64::MIN), None);
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_mul_epi32() {
+    // The even lanes are sign extended, the odd lanes must be ignored
+    let a = _mm_setr_epi32(-3, 7, -0x8000_0000, 9);
+    let b = _mm_setr_epi32(0x1_0001, -11, -0x8000_0000, 13);
+    let r = _mm_mul_epi32(a, b);
+    let e = _mm_set_epi64x(0x4000_0000_0000_0000, -3 * 0x1_0001);
+    assert_eq_m128i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_mul_epi32() {
+    let a = _mm256_setr_epi32(-1, 1, 2, 3, -4, 5, 0x1234_5678, 7);
+    let b = _mm256_setr_epi32(-1, 1, -10, 3, 0x4000_0000, 5, -0x10, 7);
+    let r = _mm256_mul_epi32(a, b);
+    let e = _mm256_setr_epi64x(1, -20, -0x1_0000_0000, -0x1_2345_6780);
+    assert_eq_m256i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_extract_epi16() {
+    let a = _mm_setr_epi16(-1, 1, 2, 3, -4, 5, 6, 7);
+    // The extracted word is zero extended
+    assert_eq!(_mm_extract_epi16(a, 0), 0xffff);
+    assert_eq!(_mm_extract_epi16(a, 4), 0xfffc);
+    assert_eq!(_mm_extract_epi16(a, 5), 5);
+    assert_eq!(_mm_extract_epi16(a, 7), 7);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_extract_epi32() {
+    let a = _mm_setr_epi32(0, -1, 2, 3);
+    assert_eq!(_mm_extract_epi32(a, 0), 0);
+    assert_eq!(_mm_extract_epi32(a, 1), -1);
+    assert_eq!(_mm_extract_epi32(a, 2), 2);
+    assert_eq!(_mm_extract_epi32(a, 3), 3);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_extract_epi64() {
+    let a = _mm_set_epi64x(-2, 0x1234_5678_9abc_def0);
+    assert_eq!(_mm_extract_epi64(a, 0), 0x1234_5678_9abc_def0);
+    assert_eq!(_mm_extract_epi64(a, 1), -2);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_insert_epi8() {
+    let a = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+    // Only the low byte of the value is inserted
+    let r = _mm_insert_epi8(a, 0x1ff, 0);
+    let e = _mm_setr_epi8(-1, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+    assert_eq_m128i(r, e);
+    let r = _mm_insert_epi8(a, 32, 7);
+    let e = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 32, 8, 9, 10, 11, 12, 13, 14, 15);
+    assert_eq_m128i(r, e);
+    let r = _mm_insert_epi8(a, 32, 15);
+    let e = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 32);
+    assert_eq_m128i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_insert_epi32() {
+    let a = _mm_setr_epi32(0, 1, 2, 3);
+    assert_eq_m128i(_mm_insert_epi32(a, -1, 0), _mm_setr_epi32(-1, 1, 2, 3));
+    assert_eq_m128i(_mm_insert_epi32(a, -1, 2), _mm_setr_epi32(0, 1, -1, 3));
+    assert_eq_m128i(_mm_insert_epi32(a, -1, 3), _mm_setr_epi32(0, 1, 2, -1));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_insert_epi64() {
+    let a = _mm_set_epi64x(1, 0);
+    assert_eq_m128i(_mm_insert_epi64(a, -1, 0), _mm_set_epi64x(1, -1));
+    assert_eq_m128i(_mm_insert_epi64(a, -1, 1), _mm_set_epi64x(-1, 0));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_cmpeq_ps() {
+    let a = _mm_setr_ps(1.0, 2.0, f32::NAN, -0.0);
+    let b = _mm_setr_ps(1.0, 3.0, f32::NAN, 0.0);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_cmpeq_ps(a, b));
+    // A true lane is the all-ones bit pattern reinterpreted as f32
+    assert_eq!(r.map(f32::to_bits), [0xFFFF_FFFF, 0, 0, 0xFFFF_FFFF]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm_cmp_ps_avx_predicates() {
+    let a = _mm_setr_ps(1.0, 2.0, f32::NAN, -0.0);
+    let b = _mm_setr_ps(1.0, 3.0, 1.0, 0.0);
+    let r = |r: __m128| std::mem::transmute::<_, [f32; 4]>(r).map(f32::to_bits);
+
+    // The AVX predicates from 8 on reach the same lowering as the SSE ones
+    assert_eq!(r(_mm_cmp_ps::<_CMP_EQ_UQ>(a, b)), [0xFFFF_FFFF, 0, 0xFFFF_FFFF, 0xFFFF_FFFF]);
+    assert_eq!(r(_mm_cmp_ps::<_CMP_GE_OQ>(a, b)), [0xFFFF_FFFF, 0, 0, 0xFFFF_FFFF]);
+    assert_eq!(
+        r(_mm_cmp_ps::<_CMP_NGT_UQ>(a, b)),
+        [0xFFFF_FFFF, 0xFFFF_FFFF, 0xFFFF_FFFF, 0xFFFF_FFFF]
+    );
+    assert_eq!(r(_mm_cmp_ps::<_CMP_NEQ_OS>(a, b)), [0, 0xFFFF_FFFF, 0, 0]);
+    assert_eq!(r(_mm_cmp_ps::<_CMP_TRUE_UQ>(a, b)), [0xFFFF_FFFF; 4]);
+    assert_eq!(r(_mm_cmp_ps::<_CMP_FALSE_OS>(a, b)), [0; 4]);
+
+    let a = _mm256_setr_ps(0.0, 1.0, 2.0, 3.0, 4.0, 5.0, f32::NAN, 7.0);
+    let r = std::mem::transmute::<_, [u32; 8]>(_mm256_cmp_ps::<_CMP_GT_OQ>(a, _mm256_set1_ps(3.0)));
+    assert_eq!(r, [0, 0, 0, 0, u32::MAX, u32::MAX, 0, u32::MAX]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_maskmoveu_si128() {
+    let a = _mm_set1_epi8(9);
+    // Only bytes with the sign bit of the mask set are written
+    let mask = _mm_setr_epi8(-1, 0, 0x7f, -0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1);
+    let mut r = [0_i8; 16];
+    _mm_maskmoveu_si128(a, mask, r.as_mut_ptr());
+    assert_eq!(r, [9, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 9]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_maskmoveu_si128_subslice() {
+    let a = _mm_setr_epi8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+    let mask = _mm_setr_epi8(-1, 0, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1, -1);
+    // The destination is derived from a subslice at an unaligned offset
+    let mut buf = [0_i8; 32];
+    let dst = &mut buf[5..21];
+    _mm_maskmoveu_si128(a, mask, dst.as_mut_ptr());
+    let mut e = [0_i8; 32];
+    (e[5], e[7], e[19], e[20]) = (1, 3, 15, 16);
+    assert_eq!(buf, e);
+}
+
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_maskmoveu_si128_page_boundary() {
+    extern "C" {
+        fn mmap(addr: *mut u8, len: usize, prot: i32, flags: i32, fd: i32, off: i64) -> *mut u8;
+        fn mprotect(addr: *mut u8, len: usize, prot: i32) -> i32;
+        fn munmap(addr: *mut u8, len: usize) -> i32;
+    }
+    const PAGE_SIZE: usize = 4096;
+    const PROT_NONE: i32 = 0;
+    const PROT_READ_WRITE: i32 = 0x1 | 0x2;
+    const MAP_PRIVATE_ANONYMOUS: i32 = 0x02 | 0x20;
+
+    // Map three pages and make the first and the last inaccessible. Accessing any byte outside
+    // of the 16 bytes written by maskmovdqu would segfault.
+    let pages =
+        mmap(std::ptr::null_mut(), 3 * PAGE_SIZE, PROT_READ_WRITE, MAP_PRIVATE_ANONYMOUS, -1, 0);
+    assert_ne!(pages as isize, -1);
+    assert_eq!(mprotect(pages, PAGE_SIZE, PROT_NONE), 0);
+    assert_eq!(mprotect(pages.add(2 * PAGE_SIZE), PAGE_SIZE, PROT_NONE), 0);
+
+    let a = _mm_setr_epi8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+    let all = _mm_set1_epi8(-1);
+    let first_and_last = _mm_setr_epi8(-1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1);
+    let e = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+    for dest in [pages.add(PAGE_SIZE), pages.add(2 * PAGE_SIZE - 16)] {
+        _mm_maskmoveu_si128(a, all, dest as *mut i8);
+        assert_eq!(*(dest as *const [i8; 16]), e);
+
+        std::ptr::write_bytes(dest, 0, 16);
+        _mm_maskmoveu_si128(a, first_and_last, dest as *mut i8);
+        assert_eq!(*(dest as *const [i8; 16]), [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 16]);
+    }
+
+    assert_eq!(munmap(pages, 3 * PAGE_SIZE), 0);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_permutevar8x32_epi32() {
+    let a = _mm256_setr_epi32(100, 101, 102, 103, 104, 105, 106, 107);
+    // Lanes move across the 128bit halves and only the low 3 bits of every index are used
+    let idx = _mm256_setr_epi32(7, 6, 5, 4, 0, 1, 8 + 2, -1);
+    let r = _mm256_permutevar8x32_epi32(a, idx);
+    let e = _mm256_setr_epi32(107, 106, 105, 104, 100, 101, 102, 107);
+    assert_eq_m256i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_permutevar8x32_ps() {
+    let a = _mm256_setr_ps(0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0);
+    let idx = _mm256_setr_epi32(4, 5, 6, 7, 3, 3, 0, 9);
+    let r = _mm256_permutevar8x32_ps(a, idx);
+    let r = std::mem::transmute::<_, [f32; 8]>(r);
+    assert_eq!(r, [4.0, 5.0, 6.0, 7.0, 3.0, 3.0, 0.0, 1.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_permute4x64_epi64() {
+    let a = _mm256_setr_epi64x(100, 101, 102, 103);
+    // Both halves receive a lane from the other half
+    let r = _mm256_permute4x64_epi64::<0b00_01_11_10>(a);
+    assert_eq_m256i(r, _mm256_setr_epi64x(102, 103, 101, 100));
+    let r = _mm256_permute4x64_epi64::<0b11_11_00_00>(a);
+    assert_eq_m256i(r, _mm256_setr_epi64x(100, 100, 103, 103));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_permute4x64_pd() {
+    let a = _mm256_setr_pd(0.0, 1.0, 2.0, 3.0);
+    let r = _mm256_permute4x64_pd::<0b00_01_10_11>(a);
+    assert_eq!(std::mem::transmute::<_, [f64; 4]>(r), [3.0, 2.0, 1.0, 0.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm_permute_ps() {
+    let a = _mm_setr_ps(0.0, 1.0, 2.0, 3.0);
+    let r = _mm_permute_ps::<0b00_01_11_10>(a);
+    assert_eq!(std::mem::transmute::<_, [f32; 4]>(r), [2.0, 3.0, 1.0, 0.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_permute_pd() {
+    let a = _mm256_setr_pd(0.0, 1.0, 2.0, 3.0);
+    let r = _mm256_permute_pd::<0b01_10>(a);
+    assert_eq!(std::mem::transmute::<_, [f64; 4]>(r), [0.0, 1.0, 3.0, 2.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm_permutevar_ps() {
+    let a = _mm_setr_ps(0.0, 1.0, 2.0, 3.0);
+    // Only the low 2 bits of every control lane are used
+    let control = _mm_setr_epi32(3, 2 + 4, 0, -1);
+    let r = _mm_permutevar_ps(a, control);
+    assert_eq!(std::mem::transmute::<_, [f32; 4]>(r), [3.0, 2.0, 0.0, 3.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_permutevar_ps() {
+    let a = _mm256_setr_ps(0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0);
+    // Every 128bit half selects from its own lanes
+    let control = _mm256_setr_epi32(1, 1, 0, 2, 3, 0, 1, 2);
+    let r = _mm256_permutevar_ps(a, control);
+    let r = std::mem::transmute::<_, [f32; 8]>(r);
+    assert_eq!(r, [1.0, 1.0, 0.0, 2.0, 7.0, 4.0, 5.0, 6.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm_permutevar_pd() {
+    let a = _mm_setr_pd(0.0, 1.0);
+    // Bit 1 and not bit 0 of every control lane selects the source lane
+    let control = _mm_set_epi64x(1, 2);
+    let r = _mm_permutevar_pd(a, control);
+    assert_eq!(std::mem::transmute::<_, [f64; 2]>(r), [1.0, 0.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_permutevar_pd() {
+    let a = _mm256_setr_pd(0.0, 1.0, 2.0, 3.0);
+    let control = _mm256_setr_epi64x(2, 0, 0, 3);
+    let r = _mm256_permutevar_pd(a, control);
+    assert_eq!(std::mem::transmute::<_, [f64; 4]>(r), [1.0, 0.0, 2.0, 3.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_unpack_epi8() {
+    let a = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+    let b = _mm_setr_epi8(16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31);
+    let r = _mm_unpacklo_epi8(a, b);
+    let e = _mm_setr_epi8(0, 16, 1, 17, 2, 18, 3, 19, 4, 20, 5, 21, 6, 22, 7, 23);
+    assert_eq_m128i(r, e);
+    let r = _mm_unpackhi_epi8(a, b);
+    let e = _mm_setr_epi8(8, 24, 9, 25, 10, 26, 11, 27, 12, 28, 13, 29, 14, 30, 15, 31);
+    assert_eq_m128i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_unpack_epi64() {
+    let a = _mm_set_epi64x(1, 0);
+    let b = _mm_set_epi64x(3, 2);
+    assert_eq_m128i(_mm_unpacklo_epi64(a, b), _mm_set_epi64x(2, 0));
+    assert_eq_m128i(_mm_unpackhi_epi64(a, b), _mm_set_epi64x(3, 1));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_cmp_ss() {
+    let a = _mm_setr_ps(1.0, 2.0, 3.0, 4.0);
+    let b = _mm_setr_ps(1.0, 5.0, 6.0, 7.0);
+    // Only lane 0 is compared, the upper lanes are copied from the first operand
+    let r = std::mem::transmute::<_, [u32; 4]>(_mm_cmpeq_ss(a, b));
+    assert_eq!(r, [0xFFFF_FFFF, 2.0f32.to_bits(), 3.0f32.to_bits(), 4.0f32.to_bits()]);
+    let r = std::mem::transmute::<_, [u32; 4]>(_mm_cmplt_ss(a, b));
+    assert_eq!(r, [0, 2.0f32.to_bits(), 3.0f32.to_bits(), 4.0f32.to_bits()]);
+    let r = std::mem::transmute::<_, [u32; 4]>(_mm_cmpunord_ss(_mm_set_ss(f32::NAN), b));
+    assert_eq!(r, [0xFFFF_FFFF, 0, 0, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cmp_sd() {
+    let a = _mm_setr_pd(1.0, 2.0);
+    let b = _mm_setr_pd(3.0, 4.0);
+    let r = std::mem::transmute::<_, [u64; 2]>(_mm_cmplt_sd(a, b));
+    assert_eq!(r, [0xFFFF_FFFF_FFFF_FFFF, 2.0f64.to_bits()]);
+    let r = std::mem::transmute::<_, [u64; 2]>(_mm_cmpge_sd(a, b));
+    assert_eq!(r, [0, 2.0f64.to_bits()]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm_cmp_pd_avx_predicates() {
+    let mask = |r: [bool; 2]| r.map(|b| if b { u64::MAX } else { 0 });
+    let a = _mm_setr_pd(1.0, f64::NAN);
+    let b = _mm_setr_pd(1.0, 2.0);
+    let r = |r: __m128d| std::mem::transmute::<_, [u64; 2]>(r);
+
+    assert_eq!(r(_mm_cmp_pd::<_CMP_EQ_UQ>(a, b)), mask([true, true]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_NGE_US>(a, b)), mask([false, true]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_NGT_US>(a, b)), mask([true, true]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_FALSE_OQ>(a, b)), mask([false, false]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_NEQ_OQ>(a, b)), mask([false, false]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_GE_OS>(a, b)), mask([true, false]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_GT_OS>(a, b)), mask([false, false]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_TRUE_UQ>(a, b)), mask([true, true]));
+    // The predicates from 16 on are the signaling or quiet counterparts of the first 16
+    assert_eq!(r(_mm_cmp_pd::<_CMP_LT_OQ>(a, b)), mask([false, false]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_UNORD_S>(a, b)), mask([false, true]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_NEQ_US>(a, b)), mask([false, true]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_FALSE_OS>(a, b)), mask([false, false]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_GE_OQ>(a, b)), mask([true, false]));
+    assert_eq!(r(_mm_cmp_pd::<_CMP_TRUE_US>(a, b)), mask([true, true]));
+
+    let a = _mm256_setr_pd(1.0, f64::NAN, 3.0, 4.0);
+    let b = _mm256_set1_pd(3.0);
+    let r = std::mem::transmute::<_, [u64; 4]>(_mm256_cmp_pd::<_CMP_LE_OQ>(a, b));
+    assert_eq!(r, [u64::MAX, 0, u64::MAX, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm_cmp_ss_sd_avx_predicates() {
+    // Only lane 0 is compared, the upper lanes are copied from the first operand
+    let a = _mm_setr_ps(2.0, 5.0, 6.0, 7.0);
+    let r = std::mem::transmute::<_, [u32; 4]>(_mm_cmp_ss::<_CMP_GT_OQ>(a, _mm_set1_ps(1.0)));
+    assert_eq!(r, [u32::MAX, 5.0f32.to_bits(), 6.0f32.to_bits(), 7.0f32.to_bits()]);
+    let r = std::mem::transmute::<_, [u32; 4]>(_mm_cmp_ss::<_CMP_NLE_UQ>(a, _mm_set1_ps(f32::NAN)));
+    assert_eq!(r[0], u32::MAX);
+    let r = std::mem::transmute::<_, [u32; 4]>(_mm_cmp_ss::<_CMP_EQ_OS>(a, _mm_set1_ps(f32::NAN)));
+    assert_eq!(r[0], 0);
+
+    let a = _mm_setr_pd(1.0, 2.0);
+    let r = std::mem::transmute::<_, [u64; 2]>(_mm_cmp_sd::<_CMP_NGE_UQ>(a, _mm_set1_pd(3.0)));
+    assert_eq!(r, [u64::MAX, 2.0f64.to_bits()]);
+    let r = std::mem::transmute::<_, [u64; 2]>(_mm_cmp_sd::<_CMP_FALSE_OQ>(a, a));
+    assert_eq!(r, [0, 2.0f64.to_bits()]);
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn test_kand_mask16() {
+    let r = _kand_mask16(0b1100_1010_0101_0011, 0b1010_1111_0000_0101);
+    assert_eq!(r, 0b1000_1010_0000_0001);
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn test_kor_mask16() {
+    let r = _kor_mask16(0b1100_1010_0101_0011, 0b1010_1111_0000_0101);
+    assert_eq!(r, 0b1110_1111_0101_0111);
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn test_kxor_mask16() {
+    let r = _kxor_mask16(0b1100_1010_0101_0011, 0b1010_1111_0000_0101);
+    assert_eq!(r, 0b0110_0101_0101_0110);
+}
+
+#[cfg(target_arch = "x86_64")]
+unsafe fn test_knot_mask16() {
+    assert_eq!(_knot_mask16(0b1100_1010_0101_0011), 0b0011_0101_1010_1100);
+    assert_eq!(_knot_mask16(0), 0xffff);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn test_mm_sign_epi8() {
+    let a = _mm_setr_epi8(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, -12, -13, 14, -128, -128);
+    let b = _mm_setr_epi8(-1, 0, 1, -128, 0, 127, -5, 0, 1, 1, 1, -1, -1, 0, -1, 1);
+    let r = _mm_sign_epi8(a, b);
+    // -128 negates to itself
+    let e = _mm_setr_epi8(-1, 0, 3, -4, 0, 6, -7, 0, 9, 10, 11, 12, 13, 0, -128, -128);
+    assert_eq_m128i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn test_mm_sign_epi16() {
+    let a = _mm_setr_epi16(1, 2, -3, 4, 5, -6, 7, 8);
+    let b = _mm_setr_epi16(-1, 0, -1, 1, 0, 1, -0x8000, 0x7fff);
+    let r = _mm_sign_epi16(a, b);
+    let e = _mm_setr_epi16(-1, 0, 3, 4, 0, -6, -7, 8);
+    assert_eq_m128i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn test_mm_sign_epi32() {
+    let a = _mm_setr_epi32(1, -2, 3, 4);
+    let b = _mm_setr_epi32(-1, -1, 0, 1);
+    let r = _mm_sign_epi32(a, b);
+    let e = _mm_setr_epi32(-1, 2, 0, 4);
+    assert_eq_m128i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_sign_epi16() {
+    let a = _mm256_setr_epi16(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+    let b = _mm256_setr_epi16(-1, 0, 1, -1, 0, 1, -1, 0, 1, -1, 0, 1, -1, 0, 1, -1);
+    let r = _mm256_sign_epi16(a, b);
+    let e = _mm256_setr_epi16(-1, 0, 3, -4, 0, 6, -7, 0, 9, -10, 0, 12, -13, 0, 15, -16);
+    assert_eq_m256i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_sign_epi8_epi32() {
+    // A negative b negates, zero zeroes and a positive b keeps the lane of a. Negating MIN wraps.
+    #[rustfmt::skip]
+    let a = _mm256_setr_epi8(
+        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+        17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, -128, -128,
+    );
+    #[rustfmt::skip]
+    let b = _mm256_setr_epi8(
+        -1, 0, 1, -128, 0, 127, -5, 0, 5, -1, 0, 1, -1, 0, 1, -1,
+        0, 1, -1, 0, 1, -1, 0, 1, -1, 0, 1, -1, 0, 1, -1, 1,
+    );
+    #[rustfmt::skip]
+    let e = _mm256_setr_epi8(
+        -1, 0, 3, -4, 0, 6, -7, 0, 9, -10, 0, 12, -13, 0, 15, -16,
+        0, 18, -19, 0, 21, -22, 0, 24, -25, 0, 27, -28, 0, 30, -128, -128,
+    );
+    assert_eq_m256i(_mm256_sign_epi8(a, b), e);
+
+    let a = _mm256_setr_epi32(1, -2, 3, -4, 5, i32::MIN, 7, 8);
+    let b = _mm256_setr_epi32(-1, -1, 0, 0, 1, -1, i32::MIN, i32::MAX);
+    let e = _mm256_setr_epi32(-1, 2, 0, 0, 5, i32::MIN, -7, 8);
+    assert_eq_m256i(_mm256_sign_epi32(a, b), e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn test_mm_abs_epi8() {
+    let a = _mm_setr_epi8(0, 1, -1, 127, -127, -128, 5, -6, 7, -8, 9, -10, 11, -12, 13, -14);
+    let r = _mm_abs_epi8(a);
+    // i8::MIN wraps to itself
+    let e = _mm_setr_epi8(0, 1, 1, 127, 127, -128, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14);
+    assert_eq_m128i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn test_mm_abs_epi16() {
+    let a = _mm_setr_epi16(0, 1, -1, i16::MAX, -i16::MAX, i16::MIN, 300, -300);
+    let r = _mm_abs_epi16(a);
+    let e = _mm_setr_epi16(0, 1, 1, i16::MAX, i16::MAX, i16::MIN, 300, 300);
+    assert_eq_m128i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn test_mm_abs_epi32() {
+    let a = _mm_setr_epi32(-1, i32::MIN, 7, -0x1234_5678);
+    let r = _mm_abs_epi32(a);
+    let e = _mm_setr_epi32(1, i32::MIN, 7, 0x1234_5678);
+    assert_eq_m128i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_abs_epi32() {
+    let a = _mm256_setr_epi32(-1, i32::MIN, 7, -8, 0, i32::MAX, -i32::MAX, 3);
+    let r = _mm256_abs_epi32(a);
+    let e = _mm256_setr_epi32(1, i32::MIN, 7, 8, 0, i32::MAX, i32::MAX, 3);
+    assert_eq_m256i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_abs_epi8_epi16() {
+    // The MIN lanes in the upper 128bit half wrap to themselves as well
+    #[rustfmt::skip]
+    let a = _mm256_setr_epi8(
+        0, 1, -1, 127, -127, 5, -6, 7, -8, 9, -10, 11, -12, 13, -14, 15,
+        -16, 17, -18, 19, -20, 21, -22, 23, -24, 25, -26, 27, -28, 29, -30, -128,
+    );
+    #[rustfmt::skip]
+    let e = _mm256_setr_epi8(
+        0, 1, 1, 127, 127, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+        16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, -128,
+    );
+    assert_eq_m256i(_mm256_abs_epi8(a), e);
+
+    #[rustfmt::skip]
+    let a = _mm256_setr_epi16(
+        0, 1, -1, i16::MAX, -i16::MAX, 300, -300, 7,
+        -8, 9, -10, 11, -12, 13, i16::MIN, -15,
+    );
+    #[rustfmt::skip]
+    let e = _mm256_setr_epi16(
+        0, 1, 1, i16::MAX, i16::MAX, 300, 300, 7,
+        8, 9, 10, 11, 12, 13, i16::MIN, 15,
+    );
+    assert_eq_m256i(_mm256_abs_epi16(a), e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn test_mm_alignr_epi8() {
+    let a = _mm_setr_epi8(16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31);
+    let b = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+
+    // The window is taken from the 32 byte concatenation a:b
+    assert_eq_m128i(_mm_alignr_epi8::<0>(a, b), b);
+    let e = _mm_setr_epi8(4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19);
+    assert_eq_m128i(_mm_alignr_epi8::<4>(a, b), e);
+    assert_eq_m128i(_mm_alignr_epi8::<16>(a, b), a);
+    // Bytes past the end of the concatenation are zero
+    let e = _mm_setr_epi8(17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 0);
+    assert_eq_m128i(_mm_alignr_epi8::<17>(a, b), e);
+    assert_eq_m128i(_mm_alignr_epi8::<33>(a, b), _mm_setzero_si128());
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_minpos_epu16() {
+    // The comparison is unsigned, so -1 is the largest value
+    let a = _mm_setr_epi16(-1, 23, 3, 8, 3, 9, 3, 0x7fff);
+    let r = _mm_minpos_epu16(a);
+    // The lowest index of a duplicated minimum is returned
+    let e = _mm_setr_epi16(3, 2, 0, 0, 0, 0, 0, 0);
+    assert_eq_m128i(r, e);
+
+    let a = _mm_setr_epi16(5, 5, 5, 5, 5, 5, 5, 0);
+    let r = _mm_minpos_epu16(a);
+    let e = _mm_setr_epi16(0, 7, 0, 0, 0, 0, 0, 0);
+    assert_eq_m128i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_blend_ps() {
+    let a = _mm_setr_ps(0.0, 1.0, 2.0, 3.0);
+    let b = _mm_setr_ps(4.0, 5.0, 6.0, 7.0);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_blend_ps::<0b1010>(a, b));
+    assert_eq!(r, [0.0, 5.0, 2.0, 7.0]);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_blend_ps::<0b0001>(a, b));
+    assert_eq!(r, [4.0, 1.0, 2.0, 3.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_blend_pd() {
+    let a = _mm_setr_pd(0.0, 1.0);
+    let b = _mm_setr_pd(2.0, 3.0);
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_blend_pd::<0b10>(a, b));
+    assert_eq!(r, [0.0, 3.0]);
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_blend_pd::<0b01>(a, b));
+    assert_eq!(r, [2.0, 1.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_blend_epi16() {
+    let a = _mm_setr_epi16(0, 1, 2, 3, 4, 5, 6, 7);
+    let b = _mm_setr_epi16(8, 9, 10, 11, 12, 13, 14, 15);
+    let r = _mm_blend_epi16::<0b1001_0110>(a, b);
+    let e = _mm_setr_epi16(0, 9, 10, 3, 12, 5, 6, 15);
+    assert_eq_m128i(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_blendv_pd_sign_bit() {
+    let a = _mm_setr_pd(0.0, 1.0);
+    let b = _mm_setr_pd(2.0, 3.0);
+    // Only the sign bit of the control lane is used and not its value, so -0.0 selects b while
+    // +0.0 selects a
+    let r = _mm_blendv_pd(a, b, _mm_setr_pd(-0.0, 0.0));
+    assert_eq!(std::mem::transmute::<_, [f64; 2]>(r), [2.0, 1.0]);
+    let r = _mm_blendv_pd(a, b, _mm_setr_pd(0.0, -0.0));
+    assert_eq!(std::mem::transmute::<_, [f64; 2]>(r), [0.0, 3.0]);
+    let r = _mm_blendv_pd(a, b, _mm_setr_pd(-f64::NAN, f64::NAN));
+    assert_eq!(std::mem::transmute::<_, [f64; 2]>(r), [2.0, 1.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm_blendv_ps_epi8() {
+    let a = _mm_setr_ps(0.0, 1.0, 2.0, 3.0);
+    let b = _mm_setr_ps(4.0, 5.0, 6.0, 7.0);
+    let r = _mm_blendv_ps(a, b, _mm_setr_ps(-1.0, 1.0, -0.0, f32::NEG_INFINITY));
+    assert_eq!(std::mem::transmute::<_, [f32; 4]>(r), [4.0, 1.0, 6.0, 7.0]);
+
+    let a = _mm256_setr_pd(0.0, 1.0, 2.0, 3.0);
+    let b = _mm256_setr_pd(4.0, 5.0, 6.0, 7.0);
+    let r = _mm256_blendv_pd(a, b, _mm256_setr_pd(1.0, -1.0, -0.0, 0.0));
+    assert_eq!(std::mem::transmute::<_, [f64; 4]>(r), [0.0, 5.0, 6.0, 3.0]);
+
+    let a = _mm_set1_epi8(1);
+    let b = _mm_set1_epi8(2);
+    let mask = _mm_setr_epi8(-1, 0, 0x7f, -0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1);
+    let e = _mm_setr_epi8(2, 1, 1, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2);
+    assert_eq_m128i(_mm_blendv_epi8(a, b, mask), e);
+    let r = _mm256_blendv_epi8(_mm256_set1_epi8(1), _mm256_set1_epi8(2), _mm256_set_m128i(mask, a));
+    assert_eq_m256i(r, _mm256_set_m128i(e, _mm_set1_epi8(1)));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cvtepi32_ps() {
+    let a = _mm_setr_epi32(-1, 2, i32::MIN, 16_777_217);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_cvtepi32_ps(a));
+    assert_eq!(r, [-1.0, 2.0, -2147483648.0, 16777216.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cvtepi32_pd() {
+    // Only the low two lanes are converted
+    let a = _mm_setr_epi32(-7, i32::MAX, 3, 4);
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_cvtepi32_pd(a));
+    assert_eq!(r, [-7.0, 2147483647.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cvtps_epi32() {
+    let a = _mm_setr_ps(1.5, -2.5, f32::NAN, 3e9);
+    assert_eq_m128i(_mm_cvtps_epi32(a), _mm_setr_epi32(2, -2, i32::MIN, i32::MIN));
+    assert_eq_m128i(_mm_cvttps_epi32(a), _mm_setr_epi32(1, -2, i32::MIN, i32::MIN));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cvtpd_epi32() {
+    // The upper two lanes of the result are zeroed
+    let a = _mm_setr_pd(-0.5, 2147483647.0);
+    assert_eq_m128i(_mm_cvtpd_epi32(a), _mm_setr_epi32(0, i32::MAX, 0, 0));
+    let a = _mm_setr_pd(-2147483648.9, 2147483648.0);
+    assert_eq_m128i(_mm_cvttpd_epi32(a), _mm_setr_epi32(i32::MIN, i32::MIN, 0, 0));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cvtpd_epi32_halfway() {
+    // Halfway values round to even with the default rounding mode
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_cvtpd_epi32(_mm_setr_pd(2.5, 3.5)));
+    assert_eq!(r, [2, 4, 0, 0]);
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_cvtpd_epi32(_mm_setr_pd(-2.5, -1.5)));
+    assert_eq!(r, [-2, -2, 0, 0]);
+
+    // -2147483648.5 rounds to i32::MIN which is in range, while 2147483647.5 rounds to 2^31
+    // which isn't and results in the integer indefinite value like NaN does
+    let a = _mm_setr_pd(-2147483648.5, 2147483647.5);
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_cvtpd_epi32(a));
+    assert_eq!(r, [i32::MIN, i32::MIN, 0, 0]);
+    let a = _mm_setr_pd(f64::NAN, 2147483646.5);
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_cvtpd_epi32(a));
+    assert_eq!(r, [i32::MIN, 2147483646, 0, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cvttpd_epi32() {
+    // Truncates toward zero and zeroes the upper two lanes
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_cvttpd_epi32(_mm_setr_pd(-1.9, 2.9)));
+    assert_eq!(r, [-1, 2, 0, 0]);
+
+    // NaN and out of range values result in the integer indefinite value
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_cvttpd_epi32(_mm_setr_pd(f64::NAN, -1e10)));
+    assert_eq!(r, [i32::MIN, i32::MIN, 0, 0]);
+    let a = _mm_setr_pd(f64::INFINITY, 2147483647.9);
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_cvttpd_epi32(a));
+    assert_eq!(r, [i32::MIN, i32::MAX, 0, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_cvtepi32_ps() {
+    let a = _mm256_setr_epi32(-1, 2, -3, 4, i32::MIN, 6, -7, 16_777_217);
+    let r = std::mem::transmute::<_, [f32; 8]>(_mm256_cvtepi32_ps(a));
+    assert_eq!(r, [-1.0, 2.0, -3.0, 4.0, -2147483648.0, 6.0, -7.0, 16777216.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_cvtps_epi32() {
+    let a = _mm256_setr_ps(0.5, 1.5, -1.7, 2.2, f32::INFINITY, -3e9, f32::NAN, -2147483648.0);
+    let e = _mm256_setr_epi32(0, 2, -2, 2, i32::MIN, i32::MIN, i32::MIN, i32::MIN);
+    assert_eq_m256i(_mm256_cvtps_epi32(a), e);
+    let e = _mm256_setr_epi32(0, 1, -1, 2, i32::MIN, i32::MIN, i32::MIN, i32::MIN);
+    assert_eq_m256i(_mm256_cvttps_epi32(a), e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_cvtpd_epi32() {
+    let a = _mm256_setr_pd(2.5, -1.5, 2147483647.4, f64::NAN);
+    assert_eq_m128i(_mm256_cvtpd_epi32(a), _mm_setr_epi32(2, -2, i32::MAX, i32::MIN));
+    let a = _mm256_setr_pd(2.9, -1.9, 2147483647.9, -2147483649.0);
+    assert_eq_m128i(_mm256_cvttpd_epi32(a), _mm_setr_epi32(2, -1, i32::MAX, i32::MIN));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_max_epi16() {
+    let a = _mm_setr_epi16(-1, 2, i16::MIN, i16::MAX, 0, -5, 7, 3);
+    let b = _mm_setr_epi16(1, -2, 0, 0, -1, -4, 7, 4);
+    assert_eq_m128i(_mm_max_epi16(a, b), _mm_setr_epi16(1, 2, 0, i16::MAX, 0, -4, 7, 4));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_min_epi16() {
+    let a = _mm_setr_epi16(-1, 2, i16::MIN, i16::MAX, 0, -5, 7, 3);
+    let b = _mm_setr_epi16(1, -2, 0, 0, -1, -4, 7, 4);
+    assert_eq_m128i(_mm_min_epi16(a, b), _mm_setr_epi16(-1, -2, i16::MIN, 0, -1, -5, 7, 3));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_max_epu8() {
+    // 0x80 and above are large unsigned values, not negative ones
+    let a = _mm_setr_epi8(1, -1, 0x7f, -128, 5, 0, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18);
+    let b = _mm_setr_epi8(2, 0, -128, 0x7f, 5, -2, 8, 10, 0, 0, 0, 0, 0, 0, 0, 0);
+    let e = _mm_setr_epi8(2, -1, -128, -128, 5, -2, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18);
+    assert_eq_m128i(_mm_max_epu8(a, b), e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_min_epu8() {
+    let a = _mm_setr_epi8(1, -1, 0x7f, -128, 5, 0, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18);
+    let b = _mm_setr_epi8(2, 0, -128, 0x7f, 5, -2, 8, 10, 0, 0, 0, 0, 0, 0, 0, 0);
+    let e = _mm_setr_epi8(1, 0, 0x7f, 0x7f, 5, 0, 8, 10, 0, 0, 0, 0, 0, 0, 0, 0);
+    assert_eq_m128i(_mm_min_epu8(a, b), e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_max_ps() {
+    // The second operand is returned when either operand is NaN or both are zero
+    let a = _mm_setr_ps(f32::NAN, 1.0, 0.0, -0.0);
+    let b = _mm_setr_ps(1.0, f32::NAN, -0.0, 0.0);
+    let r = std::mem::transmute::<_, [u32; 4]>(_mm_max_ps(a, b));
+    assert_eq!(r, [1.0, f32::NAN, -0.0, 0.0].map(f32::to_bits));
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_max_ps(_mm_setr_ps(1.0, -2.0, 3.0, 4.0), a));
+    assert_eq!(r[1..], [1.0, 3.0, 4.0]);
+    assert!(r[0].is_nan());
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_min_ps() {
+    let a = _mm_setr_ps(f32::NAN, 1.0, 0.0, -0.0);
+    let b = _mm_setr_ps(1.0, f32::NAN, -0.0, 0.0);
+    let r = std::mem::transmute::<_, [u32; 4]>(_mm_min_ps(a, b));
+    assert_eq!(r, [1.0, f32::NAN, -0.0, 0.0].map(f32::to_bits));
+    let r = std::mem::transmute::<_, [u32; 4]>(_mm_min_ps(_mm_setr_ps(1.0, -2.0, 3.0, -4.0), b));
+    assert_eq!(r, [1.0, f32::NAN, -0.0, -4.0].map(f32::to_bits));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_max_ss() {
+    let a = _mm_setr_ps(f32::NAN, 2.0, 3.0, 4.0);
+    let b = _mm_setr_ps(1.0, 20.0, 30.0, 40.0);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_max_ss(a, b));
+    assert_eq!(r, [1.0, 2.0, 3.0, 4.0]);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_max_ss(b, a));
+    assert!(r[0].is_nan());
+    assert_eq!(r[1..], [20.0, 30.0, 40.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_min_sd() {
+    let a = _mm_setr_pd(-1.0, 2.0);
+    let b = _mm_setr_pd(f64::NAN, 20.0);
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_min_sd(b, a));
+    assert_eq!(r, [-1.0, 20.0]);
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_min_sd(a, b));
+    assert!(r[0].is_nan());
+    assert_eq!(r[1], 2.0);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_max_pd() {
+    let a = _mm256_setr_pd(f64::NAN, 1.0, -0.0, 5.0);
+    let b = _mm256_setr_pd(1.0, f64::NAN, 0.0, 4.0);
+    let r = std::mem::transmute::<_, [u64; 4]>(_mm256_max_pd(a, b));
+    assert_eq!(r, [1.0, f64::NAN, 0.0, 5.0].map(f64::to_bits));
+    let r = std::mem::transmute::<_, [u64; 4]>(_mm256_min_pd(a, b));
+    assert_eq!(r, [1.0, f64::NAN, 0.0, 4.0].map(f64::to_bits));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_andnot_ps() {
+    // Lanes with all bits set in the mask are cleared, the others are kept
+    let mask = _mm_cmplt_ps(_mm_setr_ps(1.0, 5.0, -1.0, 5.0), _mm_set1_ps(2.0));
+    let r =
+        std::mem::transmute::<_, [f32; 4]>(_mm_andnot_ps(mask, _mm_setr_ps(1.5, 2.5, 3.5, 4.5)));
+    assert_eq!(r, [0.0, 2.5, 0.0, 4.5]);
+    // Clearing the sign bit is a common way to compute the absolute value
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_andnot_ps(
+        _mm_set1_ps(-0.0),
+        _mm_setr_ps(-1.0, 2.0, -0.0, f32::NEG_INFINITY),
+    ));
+    assert_eq!(r.map(f32::to_bits), [1.0, 2.0, 0.0, f32::INFINITY].map(f32::to_bits));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_andnot_pd() {
+    let mask = _mm_cmpgt_pd(_mm_setr_pd(1.0, 3.0), _mm_set1_pd(2.0));
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_andnot_pd(mask, _mm_setr_pd(-7.0, 8.0)));
+    assert_eq!(r, [-7.0, 0.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse3")]
+unsafe fn test_mm_movehdup_ps() {
+    // The odd lanes are duplicated into the even lane below them
+    let a = _mm_setr_ps(1.0, 2.0, 3.0, 4.0);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_movehdup_ps(a));
+    assert_eq!(r, [2.0, 2.0, 4.0, 4.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse3")]
+unsafe fn test_mm_moveldup_ps() {
+    // The even lanes are duplicated into the odd lane above them
+    let a = _mm_setr_ps(1.0, 2.0, 3.0, 4.0);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_moveldup_ps(a));
+    assert_eq!(r, [1.0, 1.0, 3.0, 3.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_move_epi64() {
+    let a = _mm_set_epi64x(-1, 0x1234_5678_9abc_def0);
+    let r = std::mem::transmute::<_, [i64; 2]>(_mm_move_epi64(a));
+    assert_eq!(r, [0x1234_5678_9abc_def0, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cvtsi32_si128() {
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_cvtsi32_si128(black_box(-5)));
+    assert_eq!(r, [-5, 0, 0, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_mpsadbw_epu8() {
+    let a = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+    let b = _mm_setr_epi8(20, 3, 0, 1, 8, 8, 8, 8, -1, 0, 0, 0, 0, 0, 0, 0);
+
+    // a offset 0, b offset 0
+    let r = std::mem::transmute::<_, [u16; 8]>(_mm_mpsadbw_epu8::<0b000>(a, b));
+    assert_eq!(r, [26, 26, 26, 28, 30, 32, 34, 36]);
+
+    // a offset 4, b offset 4
+    let r = std::mem::transmute::<_, [u16; 8]>(_mm_mpsadbw_epu8::<0b101>(a, b));
+    assert_eq!(r, [10, 6, 4, 4, 6, 10, 14, 18]);
+
+    // a offset 0, b offset 8 with b[8] = 255
+    let r = std::mem::transmute::<_, [u16; 8]>(_mm_mpsadbw_epu8::<0b010>(a, b));
+    assert_eq!(r, [261, 263, 265, 267, 269, 271, 273, 275]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_mpsadbw_epu8_max_sums() {
+    // Every group of 4 absolute differences of 255 sums to 1020, which needs the full 16 bits of
+    // the intermediate sum. The bytes are unsigned, so 0 - 255 doesn't wrap to 1.
+    let a = _mm_set1_epi8(-1);
+    let b = _mm_setzero_si128();
+    let r = std::mem::transmute::<_, [u16; 8]>(_mm_mpsadbw_epu8::<0b000>(a, b));
+    assert_eq!(r, [1020; 8]);
+    let r = std::mem::transmute::<_, [u16; 8]>(_mm_mpsadbw_epu8::<0b111>(b, a));
+    assert_eq!(r, [1020; 8]);
+
+    // Only the differences of the first 4 bytes of a group are summed
+    let a = _mm_setr_epi8(-1, -1, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+    let r = std::mem::transmute::<_, [u16; 8]>(_mm_mpsadbw_epu8::<0b000>(a, b));
+    assert_eq!(r, [1020, 765, 510, 255, 0, 0, 0, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_mpsadbw_epu8() {
+    #[rustfmt::skip]
+    let a = _mm256_setr_epi8(
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+        100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115,
+    );
+    #[rustfmt::skip]
+    let b = _mm256_setr_epi8(
+        20, 3, 0, 1, 8, 8, 8, 8, -1, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 110, 110, 110, 110,
+    );
+
+    // Low half: a offset 4, b offset 4. High half: a offset 0, b offset 12.
+    let r = std::mem::transmute::<_, [u16; 16]>(_mm256_mpsadbw_epu8::<0b011_101>(a, b));
+    assert_eq!(r, [10, 6, 4, 4, 6, 10, 14, 18, 34, 30, 26, 22, 18, 14, 10, 6]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_sad_epu8_result_placement() {
+    // The sum of each group of 8 bytes is in the low 16 bits of its 64bit lane and bits 16-63 are
+    // zero. The maximum sum 8 * 255 doesn't fit in 8 bits.
+    let a = _mm_setr_epi8(0, 1, 2, 3, 4, 5, 6, 7, -1, -1, -1, -1, -1, -1, -1, -1);
+    let b = _mm_setr_epi8(7, 6, 5, 4, 3, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+    let r = std::mem::transmute::<_, [u64; 2]>(_mm_sad_epu8(a, b));
+    assert_eq!(r, [32, 8 * 255]);
+    assert_eq!(r.map(|lane| lane >> 16), [0, 0]);
+
+    // The bytes are compared as unsigned
+    let r = std::mem::transmute::<_, [u64; 2]>(_mm_sad_epu8(b, a));
+    assert_eq!(r, [32, 8 * 255]);
+    let a = _mm_set1_epi8(i8::MIN);
+    let b = _mm_set1_epi8(i8::MAX);
+    let r = std::mem::transmute::<_, [u64; 2]>(_mm_sad_epu8(a, b));
+    assert_eq!(r, [8, 8]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_sad_epu8() {
+    #[rustfmt::skip]
+    let a = _mm256_setr_epi8(
+        0, 1, 2, 3, 4, 5, 6, 7, -1, -1, -1, -1, -1, -1, -1, -1,
+        10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+    );
+    let b = _mm256_setzero_si256();
+    let r = std::mem::transmute::<_, [u64; 4]>(_mm256_sad_epu8(a, b));
+    assert_eq!(r, [28, 8 * 255, 10, 1]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_srai_epi16() {
+    let a = _mm_setr_epi16(-32768, -2, -1, 0, 1, 0x7fff, -300, 300);
+
+    let r = std::mem::transmute::<_, [i16; 8]>(_mm_srai_epi16::<1>(a));
+    assert_eq!(r, [-16384, -1, -1, 0, 0, 0x3fff, -150, 150]);
+
+    // Over-width counts fill the lanes with the sign bit
+    let r = std::mem::transmute::<_, [i16; 8]>(_mm_srai_epi16::<16>(a));
+    assert_eq!(r, [-1, -1, -1, 0, 0, 0, -1, 0]);
+    let r = std::mem::transmute::<_, [i16; 8]>(_mm_srai_epi16::<255>(a));
+    assert_eq!(r, [-1, -1, -1, 0, 0, 0, -1, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_srai_epi32() {
+    let a = _mm_setr_epi32(i32::MIN, -5, 5, i32::MAX);
+
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_srai_epi32::<2>(a));
+    assert_eq!(r, [i32::MIN / 4, -2, 1, i32::MAX / 4]);
+
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_srai_epi32::<32>(a));
+    assert_eq!(r, [-1, -1, 0, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_sra_epi16() {
+    let a = _mm_setr_epi16(-32768, -2, -1, 0, 1, 0x7fff, -300, 300);
+
+    // Only the low 64 bits of the count are used
+    let count = _mm_set_epi64x(-1, 3);
+    let r = std::mem::transmute::<_, [i16; 8]>(_mm_sra_epi16(a, count));
+    assert_eq!(r, [-4096, -1, -1, 0, 0, 0x0fff, -38, 37]);
+
+    // The whole low 64 bits are the count, not just the low 16 bits
+    let count = _mm_set_epi64x(0, 0x1_0000_0001);
+    let r = std::mem::transmute::<_, [i16; 8]>(_mm_sra_epi16(a, count));
+    assert_eq!(r, [-1, -1, -1, 0, 0, 0, -1, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_sra_epi32() {
+    let a = _mm_setr_epi32(i32::MIN, -5, 5, i32::MAX);
+
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_sra_epi32(a, _mm_set_epi64x(0, 1)));
+    assert_eq!(r, [i32::MIN / 2, -3, 2, i32::MAX / 2]);
+
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_sra_epi32(a, _mm_set_epi64x(0, 33)));
+    assert_eq!(r, [-1, -1, 0, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm_sllv_srlv_epi64() {
+    let a = _mm_set1_epi64x(-1);
+    // Counts of 64 or more, including negative ones, clear the lane instead of being masked
+    let r = std::mem::transmute::<_, [u64; 2]>(_mm_sllv_epi64(a, _mm_set_epi64x(64, 63)));
+    assert_eq!(r, [1 << 63, 0]);
+    let r = std::mem::transmute::<_, [u64; 2]>(_mm_srlv_epi64(a, _mm_set_epi64x(-1, 4)));
+    assert_eq!(r, [u64::MAX >> 4, 0]);
+
+    let a = _mm256_setr_epi64x(1, 1, -1, i64::MIN);
+    let count = _mm256_setr_epi64x(0, 65, 1, 63);
+    let r = std::mem::transmute::<_, [u64; 4]>(_mm256_sllv_epi64(a, count));
+    assert_eq!(r, [1, 0, u64::MAX - 1, 0]);
+    let r = std::mem::transmute::<_, [u64; 4]>(_mm256_srlv_epi64(a, count));
+    assert_eq!(r, [1, 0, u64::MAX >> 1, 1]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_sllv_srlv_srav_epi32() {
+    let a = _mm256_setr_epi32(-16, -16, -16, -16, 16, 16, 16, 16);
+    let count = _mm256_setr_epi32(0, 2, 31, 32, 1, 33, -1, 4);
+    let r = std::mem::transmute::<_, [i32; 8]>(_mm256_sllv_epi32(a, count));
+    assert_eq!(r, [-16, -64, 0, 0, 32, 0, 0, 256]);
+    let r = std::mem::transmute::<_, [u32; 8]>(_mm256_srlv_epi32(a, count));
+    assert_eq!(r, [-16i32 as u32, -16i32 as u32 >> 2, 1, 0, 8, 0, 0, 1]);
+    // Arithmetic shifts by 32 or more fill the lane with its sign bit
+    let r = std::mem::transmute::<_, [i32; 8]>(_mm256_srav_epi32(a, count));
+    assert_eq!(r, [-16, -4, -1, -1, 8, 0, 0, 1]);
+
+    let a = _mm_setr_epi32(-8, 8, -8, 8);
+    let r = _mm_srav_epi32(a, _mm_setr_epi32(1, 1, 100, 100));
+    assert_eq_m128i(r, _mm_setr_epi32(-4, 4, -1, 0));
+    let r = _mm_sllv_epi32(a, _mm_setr_epi32(1, 28, 32, 0));
+    assert_eq_m128i(r, _mm_setr_epi32(-16, i32::MIN, 0, 8));
+    let r = _mm_srlv_epi32(a, _mm_setr_epi32(28, 3, 32, 0));
+    assert_eq_m128i(r, _mm_setr_epi32(15, 1, 0, 8));
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+unsafe fn test_mm512_mask_add_sub_mullo_epi32() {
+    let src = _mm512_set1_epi32(-1);
+    let a = _mm512_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+    let b = _mm512_set1_epi32(3);
+    // Lanes 0, 2, 3 and 15 are selected
+    let k: __mmask16 = 0b1000_0000_0000_1101;
+
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_mask_add_epi32(src, k, a, b));
+    assert_eq!(r, [3, -1, 5, 6, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 18]);
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_maskz_add_epi32(k, a, b));
+    assert_eq!(r, [3, 0, 5, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 18]);
+
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_mask_sub_epi32(src, k, a, b));
+    assert_eq!(r, [-3, -1, -1, 0, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 12]);
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_maskz_sub_epi32(k, a, b));
+    assert_eq!(r, [-3, 0, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 12]);
+
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_mask_mullo_epi32(src, k, a, b));
+    assert_eq!(r, [0, -1, 6, 9, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 45]);
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_maskz_mullo_epi32(k, a, b));
+    assert_eq!(r, [0, 0, 6, 9, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 45]);
+
+    // An all zero mask only selects src
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_mask_add_epi32(src, 0, a, b));
+    assert_eq!(r, [-1; 16]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+unsafe fn test_mm512_kortestc() {
+    // The carry flag is set if the OR of both masks has all 16 bits set
+    assert_eq!(_mm512_kortestc(0b1010_1010_1010_1010, 0b0101_0101_0101_0101), 1);
+    assert_eq!(_mm512_kortestc(0xffff, 0), 1);
+    assert_eq!(_mm512_kortestc(0b0111_1111_1111_1111, 0b0011_1111_1111_1111), 0);
+    assert_eq!(_mm512_kortestc(0, 0), 0);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+unsafe fn test_mm512_compress_expand_epi32() {
+    let src = _mm512_set1_epi32(-1);
+    #[rustfmt::skip]
+    let a = _mm512_setr_epi32(
+        100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115,
+    );
+    // Lanes 0, 1, 5, 10 and 15 are selected
+    let k: __mmask16 = 0b1000_0100_0010_0011;
+
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_mask_compress_epi32(src, k, a));
+    assert_eq!(r, [100, 101, 105, 110, 115, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1]);
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_maskz_compress_epi32(k, a));
+    assert_eq!(r, [100, 101, 105, 110, 115, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_mask_expand_epi32(src, k, a));
+    assert_eq!(r, [100, 101, -1, -1, -1, 102, -1, -1, -1, -1, 103, -1, -1, -1, -1, 104]);
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_maskz_expand_epi32(k, a));
+    assert_eq!(r, [100, 101, 0, 0, 0, 102, 0, 0, 0, 0, 103, 0, 0, 0, 0, 104]);
+
+    // Compressing all lanes or none is the identity and src respectively
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_mask_compress_epi32(src, 0xffff, a));
+    assert_eq!(r, std::mem::transmute::<_, [i32; 16]>(a));
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_mask_expand_epi32(src, 0, a));
+    assert_eq!(r, [-1; 16]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512vl")]
+unsafe fn test_mm256_compress_expand_ps_pd() {
+    let src = _mm256_set1_ps(-1.0);
+    let a = _mm256_setr_ps(0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0);
+    let r = std::mem::transmute::<_, [f32; 8]>(_mm256_mask_compress_ps(src, 0b1001_0100, a));
+    assert_eq!(r, [2.0, 4.0, 7.0, -1.0, -1.0, -1.0, -1.0, -1.0]);
+    let r = std::mem::transmute::<_, [f32; 8]>(_mm256_mask_expand_ps(src, 0b1001_0100, a));
+    assert_eq!(r, [-1.0, -1.0, 0.0, -1.0, 1.0, -1.0, -1.0, 2.0]);
+
+    // Mask bits above the lane count are ignored
+    let a = _mm_setr_pd(1.0, 2.0);
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_maskz_compress_pd(0b1111_1110, a));
+    assert_eq!(r, [2.0, 0.0]);
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_maskz_expand_pd(0b1111_1110, a));
+    assert_eq!(r, [0.0, 1.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512bw,avx512vbmi2")]
+unsafe fn test_mm512_compress_expand_epi8_epi16() {
+    let mut a = [0i8; 64];
+    for (i, lane) in a.iter_mut().enumerate() {
+        *lane = i as i8;
+    }
+    let a = std::mem::transmute::<_, __m512i>(a);
+
+    // Lanes 3, 40 and 63 are selected
+    let k: __mmask64 = 1 << 3 | 1 << 40 | 1 << 63;
+    let r = std::mem::transmute::<_, [i8; 64]>(_mm512_maskz_compress_epi8(k, a));
+    let mut e = [0; 64];
+    e[..3].copy_from_slice(&[3, 40, 63]);
+    assert_eq!(r, e);
+    let r = std::mem::transmute::<_, [i8; 64]>(_mm512_maskz_expand_epi8(k, a));
+    let mut e = [0; 64];
+    (e[3], e[40], e[63]) = (0, 1, 2);
+    assert_eq!(r, e);
+
+    // Lanes 1 and 30 are selected
+    let src = _mm512_set1_epi16(-1);
+    let k: __mmask32 = 1 << 1 | 1 << 30;
+    let r = std::mem::transmute::<_, [i16; 32]>(_mm512_mask_compress_epi16(src, k, a));
+    let mut e = [-1; 32];
+    e[..2].copy_from_slice(&[0x0302, 0x3d3c]);
+    assert_eq!(r, e);
+    let r = std::mem::transmute::<_, [i16; 32]>(_mm512_mask_expand_epi16(src, k, a));
+    let mut e = [-1; 32];
+    (e[1], e[30]) = (0x0100, 0x0302);
+    assert_eq!(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+unsafe fn test_mm512_mask_cvtps_epi32() {
+    let src = _mm512_set1_epi32(7);
+    #[rustfmt::skip]
+    let a = _mm512_setr_ps(
+        0.5, 1.5, -2.5, f32::NAN, 1e10, -1e10, 3.7, -3.7,
+        0.5, 1.5, -2.5, f32::NAN, 1e10, -1e10, 3.7, -3.7,
+    );
+    let k = 0b0000_1111_1111_0101;
+    let indef = i32::MIN;
+
+    // Rounds to nearest even, NaN and out of range lanes become the integer indefinite value
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_mask_cvtps_epi32(src, k, a));
+    #[rustfmt::skip]
+    assert_eq!(r, [0, 7, -2, 7, indef, indef, 4, -4, 0, 2, -2, indef, 7, 7, 7, 7]);
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_maskz_cvtps_epi32(k, a));
+    #[rustfmt::skip]
+    assert_eq!(r, [0, 0, -2, 0, indef, indef, 4, -4, 0, 2, -2, indef, 0, 0, 0, 0]);
+
+    // An unselected NaN lane keeps the src lane
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_mask_cvtps_epi32(src, !(1 << 3), a));
+    assert_eq!(r[3], 7);
+
+    // Explicit rounding modes override the default round to nearest even
+    let r =
+        _mm512_mask_cvt_roundps_epi32::<{ _MM_FROUND_TO_NEG_INF | _MM_FROUND_NO_EXC }>(src, k, a);
+    let r = std::mem::transmute::<_, [i32; 16]>(r);
+    assert_eq!(r[..8], [0, 7, -3, 7, indef, indef, 3, -4]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f,avx512vl")]
+unsafe fn test_mm512_mask_cvtpd_cvttps_epi32() {
+    let a = _mm512_setr_pd(0.5, 1.5, -2.5, f64::NAN, 1e10, 3.7, -3.7, 2.5);
+    let r = std::mem::transmute::<_, [i32; 8]>(_mm512_mask_cvtpd_epi32(
+        _mm256_set1_epi32(7),
+        0b0111_1101,
+        a,
+    ));
+    assert_eq!(r, [0, 7, -2, i32::MIN, i32::MIN, 4, -4, 7]);
+
+    // The truncating conversions round towards zero
+    let a = _mm_setr_ps(-3.7, f32::NAN, 3.7, 1.5);
+    let r = _mm_maskz_cvttps_epi32(0b1011, a);
+    assert_eq_m128i(r, _mm_setr_epi32(-3, i32::MIN, 0, 1));
+    let a = _mm256_setr_ps(-3.7, f32::NAN, 3.7, 1.5, 0.5, -0.5, 1e10, 9.9);
+    let r = _mm256_mask_cvttps_epi32(_mm256_set1_epi32(7), 0b1101_0110, a);
+    assert_eq_m256i(r, _mm256_setr_epi32(7, i32::MIN, 3, 7, 0, 7, i32::MIN, 9));
+    let r = std::mem::transmute::<_, [i32; 16]>(_mm512_maskz_cvttps_epi32(
+        0b1000_0000_0000_0001,
+        _mm512_set1_ps(-2.9),
+    ));
+    assert_eq!([r[0], r[1], r[14], r[15]], [-2, 0, 0, -2]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_packus_epi32() {
+    let a = _mm_setr_epi32(-1, 0, 65535, 65536);
+    let b = _mm_setr_epi32(i32::MIN, 1234, i32::MAX, -65536);
+    let r = std::mem::transmute::<_, [u16; 8]>(_mm_packus_epi32(a, b));
+    assert_eq!(r, [0, 0, 65535, 65535, 0, 1234, 65535, 0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_packs_epi16_epi32() {
+    let a = _mm_setr_epi16(-129, -128, 127, 128, 255, 256, i16::MIN, i16::MAX);
+    let b = _mm_setr_epi16(0, 1, -1, 2, -2, 3, -3, 4);
+    let r = std::mem::transmute::<_, [i8; 16]>(_mm_packs_epi16(a, b));
+    assert_eq!(r, [-128, -128, 127, 127, 127, 127, -128, 127, 0, 1, -1, 2, -2, 3, -3, 4]);
+    let r = std::mem::transmute::<_, [u8; 16]>(_mm_packus_epi16(a, b));
+    assert_eq!(r, [0, 0, 127, 128, 255, 255, 0, 255, 0, 1, 0, 2, 0, 3, 0, 4]);
+
+    let a = _mm_setr_epi32(-32769, 32768, i32::MIN, i32::MAX);
+    let b = _mm_setr_epi32(-32768, 32767, -1, 1);
+    let r = std::mem::transmute::<_, [i16; 8]>(_mm_packs_epi32(a, b));
+    assert_eq!(r, [i16::MIN, i16::MAX, i16::MIN, i16::MAX, i16::MIN, i16::MAX, -1, 1]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_packs_packus() {
+    // Each 128bit half packs the same half of a and b. A pack of the whole register would give
+    // a0..a7 b0..b7 instead of a0..a3 b0..b3 a4..a7 b4..b7.
+    let a = _mm256_setr_epi32(1, 2, 3, 4, 5, 6, 7, 8);
+    let b = _mm256_setr_epi32(-1, -2, -3, -4, -5, -6, -7, 100000);
+    let r = std::mem::transmute::<_, [i16; 16]>(_mm256_packs_epi32(a, b));
+    assert_eq!(r, [1, 2, 3, 4, -1, -2, -3, -4, 5, 6, 7, 8, -5, -6, -7, i16::MAX]);
+    let r = std::mem::transmute::<_, [u16; 16]>(_mm256_packus_epi32(a, b));
+    assert_eq!(r, [1, 2, 3, 4, 0, 0, 0, 0, 5, 6, 7, 8, 0, 0, 0, 65535]);
+
+    #[rustfmt::skip]
+    let a = _mm256_setr_epi16(
+        1, 2, 3, 4, 5, 6, 7, 8,
+        9, 10, 11, 12, 13, 14, 15, 300,
+    );
+    #[rustfmt::skip]
+    let b = _mm256_setr_epi16(
+        -1, -2, -3, -4, -5, -6, -7, -8,
+        -9, -10, -11, -12, -13, -14, -15, -300,
+    );
+    let r = std::mem::transmute::<_, [i8; 32]>(_mm256_packs_epi16(a, b));
+    #[rustfmt::skip]
+    let e = [
+        1, 2, 3, 4, 5, 6, 7, 8, -1, -2, -3, -4, -5, -6, -7, -8,
+        9, 10, 11, 12, 13, 14, 15, 127, -9, -10, -11, -12, -13, -14, -15, -128,
+    ];
+    assert_eq!(r, e);
+    let r = std::mem::transmute::<_, [u8; 32]>(_mm256_packus_epi16(a, b));
+    #[rustfmt::skip]
+    let e = [
+        1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, 0, 0, 0, 0,
+        9, 10, 11, 12, 13, 14, 15, 255, 0, 0, 0, 0, 0, 0, 0, 0,
+    ];
+    assert_eq!(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_pmovmskb_u32_result() {
+    #[repr(simd)]
+    #[derive(Copy, Clone)]
+    struct I8x16([i8; 16]);
+
+    // The result type of the declaration is used rather than assuming i32
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "llvm.x86.sse2.pmovmskb.128"]
+        fn pmovmskb(a: I8x16) -> u32;
+    }
+
+    let a = I8x16([-1, 0, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -128]);
+    assert_eq!(pmovmskb(a), 0b1000_0000_0000_0101);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse3")]
+unsafe fn test_mm_hadd_ps() {
+    let a = _mm_setr_ps(1.0, 2.0, 3.0, 4.0);
+    let b = _mm_setr_ps(10.0, 20.0, 30.0, 40.0);
+    let r = _mm_hadd_ps(a, b);
+    assert_eq!(std::mem::transmute::<_, [f32; 4]>(r), [3.0, 7.0, 30.0, 70.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_hadd_ps() {
+    let a = _mm256_setr_ps(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0);
+    let b = _mm256_setr_ps(10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0);
+    let r = _mm256_hadd_ps(a, b);
+    // Each 128bit half only combines the pairs of the same half of a and b
+    let e = [3.0, 7.0, 30.0, 70.0, 11.0, 15.0, 110.0, 150.0];
+    assert_eq!(std::mem::transmute::<_, [f32; 8]>(r), e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_hsub_pd() {
+    let a = _mm256_setr_pd(1.0, 2.0, 10.0, 40.0);
+    let b = _mm256_setr_pd(100.0, 300.0, 1000.0, 4000.0);
+    let r = _mm256_hsub_pd(a, b);
+    assert_eq!(std::mem::transmute::<_, [f64; 4]>(r), [-1.0, -200.0, -30.0, -3000.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse3")]
+unsafe fn test_mm_hadd_hsub_lane_order() {
+    // The pairs of a end up in the low lanes and the pairs of b in the high lanes. Subtraction
+    // isn't commutative, so it also pins the order of the lanes within a pair.
+    let a = _mm_setr_ps(1.0, 2.0, 4.0, 8.0);
+    let b = _mm_setr_ps(16.0, 32.0, 64.0, 128.0);
+    let add = std::mem::transmute::<_, [f32; 4]>(_mm_hadd_ps(a, b));
+    let sub = std::mem::transmute::<_, [f32; 4]>(_mm_hsub_ps(a, b));
+    assert_eq!(add, [3.0, 12.0, 48.0, 192.0]);
+    assert_eq!(sub, [-1.0, -4.0, -16.0, -64.0]);
+
+    let a = _mm_setr_pd(1.0, 2.0);
+    let b = _mm_setr_pd(16.0, 4.0);
+    let add = std::mem::transmute::<_, [f64; 2]>(_mm_hadd_pd(a, b));
+    let sub = std::mem::transmute::<_, [f64; 2]>(_mm_hsub_pd(a, b));
+    assert_eq!(add, [3.0, 20.0]);
+    assert_eq!(sub, [-1.0, 12.0]);
+}
+
+/// Check that every lane is within the relative error of at most 1.5 * 2^-12 guaranteed for the
+/// approximations of rcpps and rsqrtps. Special values must match exactly.
+#[cfg(target_arch = "x86_64")]
+fn assert_approx_eq_rcp(r: &[f32], e: &[f32]) {
+    for (&r, &e) in r.iter().zip(e) {
+        if e.is_finite() && e != 0.0 {
+            assert!(((r - e) / e).abs() <= 1.5 / 4096.0, "{r} is not close to {e}");
+        } else {
+            assert_eq!(r.to_bits(), e.to_bits());
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_rcp_rsqrt_ps_ss() {
+    let a = _mm_setr_ps(4.0, -0.25, 0.0, f32::INFINITY);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_rcp_ps(a));
+    assert_approx_eq_rcp(&r, &[0.25, -4.0, f32::INFINITY, 0.0]);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_rsqrt_ps(a));
+    assert!(r[1].is_nan());
+    assert_approx_eq_rcp(&[r[0], r[2], r[3]], &[0.5, f32::INFINITY, 0.0]);
+
+    // Only lane 0 is computed
+    let a = _mm_setr_ps(16.0, 2.0, 3.0, 4.0);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_rcp_ss(a));
+    assert_approx_eq_rcp(&r, &[0.0625, 2.0, 3.0, 4.0]);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_rsqrt_ss(a));
+    assert_approx_eq_rcp(&r, &[0.25, 2.0, 3.0, 4.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_rcp_rsqrt_ps() {
+    let a = _mm256_setr_ps(1.0, 2.0, 4.0, 8.0, 0.5, 100.0, 3.0, -0.0);
+    let r = std::mem::transmute::<_, [f32; 8]>(_mm256_rcp_ps(a));
+    let e = [1.0, 0.5, 0.25, 0.125, 2.0, 0.01, 1.0 / 3.0, f32::NEG_INFINITY];
+    assert_approx_eq_rcp(&r, &e);
+    let r = std::mem::transmute::<_, [f32; 8]>(_mm256_rsqrt_ps(a));
+    let e = [1.0, 0.70710677, 0.5, 0.35355338, 1.4142135, 0.1, 0.57735026, f32::NEG_INFINITY];
+    assert_approx_eq_rcp(&r, &e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cvtps_pd() {
+    // The upper two lanes are ignored
+    let a = _mm_setr_ps(0.1, -2.5, 3.0, 4.0);
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_cvtps_pd(a));
+    assert_eq!(r, [0.100000001490116119384765625, -2.5]);
+
+    // Subnormals must not be flushed to zero
+    let a = _mm_setr_ps(f32::from_bits(1), -f32::from_bits(0x007f_ffff), 0.0, 0.0);
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_cvtps_pd(a));
+    assert_eq!(r, [2.0f64.powi(-149), -(2.0f64.powi(-126) - 2.0f64.powi(-149))]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_sqrt_pd() {
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_sqrt_pd(_mm_setr_pd(16.0, 2.0)));
+    assert_eq!(r, [4.0, std::f64::consts::SQRT_2]);
+
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_sqrt_pd(_mm_setr_pd(-1.0, 0.0)));
+    assert!(r[0].is_nan());
+    assert_eq!(r[1], 0.0);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_dp_ps() {
+    let a = _mm_setr_ps(1.0, 2.0, 3.0, 4.0);
+    let b = _mm_setr_ps(5.0, 6.0, 7.0, 8.0);
+    let r = |r: __m128| std::mem::transmute::<_, [f32; 4]>(r);
+
+    assert_eq!(r(_mm_dp_ps::<0xff>(a, b)), [70.0; 4]);
+    // Only lanes 0 and 2 are multiplied, only lanes 1 and 3 receive the sum
+    assert_eq!(r(_mm_dp_ps::<0b0101_1010>(a, b)), [0.0, 26.0, 0.0, 26.0]);
+    // An empty multiply mask still broadcasts the (zero) sum to the selected lanes
+    let a = _mm_setr_ps(-1.0, -2.0, -3.0, -4.0);
+    let res = r(_mm_dp_ps::<0x0f>(a, b));
+    assert_eq!(res.map(f32::to_bits), [0.0f32.to_bits(); 4]);
+    // An empty broadcast mask zeroes every lane
+    assert_eq!(r(_mm_dp_ps::<0xf0>(a, b)), [0.0; 4]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_dp_ps_nan() {
+    let a = _mm_setr_ps(1.0, f32::NAN, 3.0, f32::INFINITY);
+    let b = _mm_setr_ps(5.0, 6.0, 7.0, 0.0);
+    let r = |r: __m128| std::mem::transmute::<_, [f32; 4]>(r);
+
+    // A NaN product in a multiplied lane propagates to every lane receiving the sum
+    let res = r(_mm_dp_ps::<0b0011_0101>(a, b));
+    assert!(res[0].is_nan() && res[2].is_nan());
+    assert_eq!([res[1], res[3]], [0.0, 0.0]);
+    // Infinity times zero is NaN too
+    let res = r(_mm_dp_ps::<0b1000_1000>(a, b));
+    assert_eq!(res[..3], [0.0, 0.0, 0.0]);
+    assert!(res[3].is_nan());
+    // Lanes which aren't multiplied don't contribute their NaN
+    assert_eq!(r(_mm_dp_ps::<0b0101_1111>(a, b)), [26.0; 4]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_dp_ps_accumulation_order() {
+    let r = |r: __m128| std::mem::transmute::<_, [f32; 4]>(r);
+
+    // The products are summed as (p0 + p1) + (p2 + p3). Summing from left to right would round
+    // 1 + 2^-24 back to 1 twice and return 1 instead of 1 + 2^-23.
+    let tiny = f32::EPSILON / 2.0;
+    let a = _mm_setr_ps(1.0, 0.0, tiny, tiny);
+    let b = _mm_set1_ps(1.0);
+    assert_eq!(r(_mm_dp_ps::<0xf1>(a, b))[0], 1.0 + f32::EPSILON);
+
+    // Subnormal products and sums are neither flushed to zero nor treated as zero
+    let a = _mm_set1_ps(2.0f32.powi(-70));
+    let b = _mm_setr_ps(2.0f32.powi(-70), 2.0f32.powi(-70), 2.0f32.powi(-79), 0.0);
+    let res = r(_mm_dp_ps::<0xf1>(a, b))[0];
+    // 2^-139 + 2^-149
+    assert_eq!(res, f32::from_bits((1 << 10) + 1));
+    assert!(res.is_subnormal());
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_dp_ps_per_half() {
+    let a = _mm256_setr_ps(1.0, 2.0, 3.0, 4.0, 10.0, 20.0, 30.0, 40.0);
+    let b = _mm256_setr_ps(5.0, 6.0, 7.0, 8.0, -1.0, 1.0, -2.0, 2.0);
+    let r = |r: __m256| std::mem::transmute::<_, [f32; 8]>(r);
+
+    // Every half gets its own dot product rather than the sum across both halves
+    assert_eq!(r(_mm256_dp_ps::<0xff>(a, b)), [70.0, 70.0, 70.0, 70.0, 30.0, 30.0, 30.0, 30.0]);
+    // The same immediate applies to both halves
+    let res = r(_mm256_dp_ps::<0b0011_0110>(a, b));
+    assert_eq!(res, [0.0, 17.0, 17.0, 0.0, 0.0, 10.0, 10.0, 0.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_dp_pd() {
+    let a = _mm_setr_pd(1.5, -2.0);
+    let b = _mm_setr_pd(4.0, 3.0);
+    let r = |r: __m128d| std::mem::transmute::<_, [f64; 2]>(r);
+
+    assert_eq!(r(_mm_dp_pd::<0x33>(a, b)), [0.0, 0.0]);
+    assert_eq!(r(_mm_dp_pd::<0x31>(a, b)), [0.0, 0.0]);
+    assert_eq!(r(_mm_dp_pd::<0x12>(a, b)), [0.0, 6.0]);
+    assert_eq!(r(_mm_dp_pd::<0x21>(a, b)), [-6.0, 0.0]);
+    // The same edge cases as for dpps
+    let res = r(_mm_dp_pd::<0x03>(a, b));
+    assert_eq!(res.map(f64::to_bits), [0.0f64.to_bits(); 2]);
+    assert_eq!(r(_mm_dp_pd::<0x30>(a, b)), [0.0, 0.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_getcsr_setcsr() {
+    let orig = _mm_getcsr();
+    // Switch to round toward zero and mask all exceptions
+    let new = (orig & !_MM_ROUND_MASK) | _MM_ROUND_TOWARD_ZERO | _MM_MASK_MASK;
+    _mm_setcsr(new);
+    assert_eq!(_mm_getcsr(), new);
+    _mm_setcsr(orig);
+    assert_eq!(_mm_getcsr(), orig);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_mullo_mulhi_epi16() {
+    let a = _mm_setr_epi16(3, -3, 300, -300, 0x4000, i16::MIN, i16::MAX, -1);
+    let b = _mm_setr_epi16(7, 7, 300, 300, 4, i16::MIN, i16::MAX, -1);
+    // The full 32bit products are
+    // 21, -21, 90000, -90000, 0x10000, 0x40000000, 0x3fff0001, 1
+    let lo = std::mem::transmute::<_, [i16; 8]>(_mm_mullo_epi16(a, b));
+    let hi = std::mem::transmute::<_, [i16; 8]>(_mm_mulhi_epi16(a, b));
+    assert_eq!(lo, [21, -21, 24464, -24464, 0, 0, 1, 1]);
+    assert_eq!(hi, [0, -1, 1, -2, 1, 0x4000, 0x3fff, 0]);
+
+    let hi = std::mem::transmute::<_, [u16; 8]>(_mm_mulhi_epu16(a, b));
+    assert_eq!(hi, [0, 6, 1, 298, 1, 0x4000, 0x3fff, 0xfffe]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_mullo_epi32() {
+    let a = _mm_setr_epi32(6, 0x10000, i32::MAX, -3);
+    let b = _mm_setr_epi32(-7, 0x10001, 2, 0x4000_0001);
+    // Only the low 32 bits of 0x1_0001_0000, 0xffff_fffe and -0xc000_0003 are kept
+    let r = std::mem::transmute::<_, [i32; 4]>(_mm_mullo_epi32(a, b));
+    assert_eq!(r, [-42, 0x10000, -2, 0x3fff_fffd]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_movemask_epi8_lane_order() {
+    // Lane i must end up in bit i of the mask
+    for lane in 0..16 {
+        let mut a = [0i8; 16];
+        a[lane] = -1;
+        let mask = _mm_movemask_epi8(std::mem::transmute(a));
+        assert_eq!(mask, 1 << lane, "lane {lane}");
+    }
+    for lane in 0..32 {
+        let mut a = [0i8; 32];
+        a[lane] = -1;
+        let mask = _mm256_movemask_epi8(std::mem::transmute(a));
+        assert_eq!(mask as u32, 1 << lane, "lane {lane}");
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_movemask_pd_two_lanes() {
+    // Only the low 2 bits of the result can be set. -0.0 and negative NaN have the sign bit set.
+    let neg_nan = f64::from_bits(f64::NAN.to_bits() | 1 << 63);
+    let cases = [
+        (1.0, 2.0, 0b00),
+        (-1.0, 2.0, 0b01),
+        (1.0, -0.0, 0b10),
+        (neg_nan, f64::NEG_INFINITY, 0b11),
+        (f64::NAN, -f64::MIN_POSITIVE, 0b10),
+    ];
+    for (a, b, e) in cases {
+        let mask = _mm_movemask_pd(_mm_setr_pd(a, b));
+        assert_eq!(mask, e, "a={a} b={b}");
+    }
+
+    // Reinterpreted as two i64 lanes the same holds with the sign bits of the integers
+    let mask = _mm_movemask_pd(_mm_castsi128_pd(_mm_set_epi64x(i64::MIN, -1)));
+    assert_eq!(mask, 0b11);
+    let mask = _mm_movemask_pd(_mm_castsi128_pd(_mm_set_epi64x(i64::MAX, i64::MIN)));
+    assert_eq!(mask, 0b01);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_movemask_epi8_all_bits() {
+    // Covers every one of the 32 mask bits, including bit 31 which is the sign bit of the result
+    #[rustfmt::skip]
+    let a = _mm256_setr_epi8(
+        -1, 0, 0, -128, 0, 0, 0, 0, 127, -2, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, -1, 0, 0, 0, 0, 0, 0, -1, 0, 0, -1,
+    );
+    assert_eq!(_mm256_movemask_epi8(a) as u32, 0b1001_0000_0010_0000_0000_0010_0000_1001);
+    assert_eq!(_mm256_movemask_epi8(_mm256_set1_epi8(i8::MAX)), 0);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_testz_si128() {
+    let zero = _mm_setzero_si128();
+    let ones = _mm_set1_epi8(-1);
+    let a = _mm_setr_epi32(0b0101, 0, 0, 0);
+    assert_eq!(_mm_testz_si128(a, _mm_setr_epi32(0b1010, 0, 0, 0)), 1);
+    assert_eq!(_mm_testz_si128(a, a), 0);
+    assert_eq!(_mm_testc_si128(ones, a), 1);
+    assert_eq!(_mm_testc_si128(a, ones), 0);
+    assert_eq!(_mm_testnzc_si128(a, _mm_setr_epi32(0b0110, 0, 0, 0)), 1);
+    assert_eq!(_mm_testnzc_si128(zero, ones), 0);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_testz_si256() {
+    let zero = _mm256_setzero_si256();
+    let ones = _mm256_set1_epi8(-1);
+    // Only the top dword is set, so the reduction must cover all 256 bits
+    let high = _mm256_setr_epi32(0, 0, 0, 0, 0, 0, 0, i32::MIN);
+    let mixed = _mm256_setr_epi32(1, 0, 0, 0, 0, 0, 0, i32::MIN);
+
+    assert_eq!(_mm256_testz_si256(zero, zero), 1);
+    assert_eq!(_mm256_testc_si256(zero, zero), 1);
+    assert_eq!(_mm256_testnzc_si256(zero, zero), 0);
+
+    assert_eq!(_mm256_testz_si256(ones, ones), 0);
+    assert_eq!(_mm256_testc_si256(ones, ones), 1);
+    assert_eq!(_mm256_testnzc_si256(ones, ones), 0);
+
+    assert_eq!(_mm256_testz_si256(high, ones), 0);
+    assert_eq!(_mm256_testz_si256(high, mixed), 0);
+    assert_eq!(_mm256_testc_si256(high, mixed), 0);
+    assert_eq!(_mm256_testc_si256(mixed, high), 1);
+    assert_eq!(_mm256_testnzc_si256(high, mixed), 1);
+    assert_eq!(_mm256_testnzc_si256(mixed, high), 0);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_testz_ps_pd() {
+    // The lanes only differ in the sign bit, which is the only bit the float forms look at
+    let pos = _mm256_set1_ps(1.5);
+    let neg = _mm256_setr_ps(1.5, 1.5, -1.5, 1.5, 1.5, 1.5, 1.5, 1.5);
+    assert_eq!(_mm256_testz_ps(pos, pos), 1);
+    assert_eq!(_mm256_testz_ps(neg, neg), 0);
+    assert_eq!(_mm256_testz_ps(pos, neg), 1);
+    assert_eq!(_mm256_testc_ps(pos, neg), 0);
+    assert_eq!(_mm256_testc_ps(neg, pos), 1);
+    assert_eq!(_mm256_testnzc_ps(neg, pos), 0);
+    assert_eq!(_mm256_testz_si256(_mm256_castps_si256(pos), _mm256_castps_si256(pos)), 0);
+
+    let neg2 = _mm256_setr_ps(-1.5, 1.5, -1.5, 1.5, 1.5, 1.5, 1.5, 1.5);
+    assert_eq!(_mm256_testnzc_ps(neg, neg2), 1);
+
+    let a = _mm_setr_ps(-1.0, 2.0, 3.0, 4.0);
+    let b = _mm_setr_ps(1.0, -2.0, 3.0, 4.0);
+    assert_eq!(_mm_testz_ps(a, b), 1);
+    assert_eq!(_mm_testc_ps(a, b), 0);
+
+    let a = _mm256_setr_pd(-1.0, 2.0, 3.0, 4.0);
+    let b = _mm256_setr_pd(-7.0, 8.0, 9.0, -10.0);
+    assert_eq!(_mm256_testz_pd(a, b), 0);
+    assert_eq!(_mm256_testc_pd(a, b), 0);
+    assert_eq!(_mm256_testnzc_pd(a, b), 1);
+    assert_eq!(_mm256_testz_pd(a, _mm256_set1_pd(5.0)), 1);
+    assert_eq!(_mm_testc_pd(_mm_set1_pd(-1.0), _mm_setr_pd(-2.0, 3.0)), 1);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_blend_epi32() {
+    let a = _mm256_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7);
+    let b = _mm256_setr_epi32(10, 11, 12, 13, 14, 15, 16, 17);
+    let r = |r: __m256i| std::mem::transmute::<_, [i32; 8]>(r);
+
+    assert_eq!(r(_mm256_blend_epi32::<0>(a, b)), [0, 1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(r(_mm256_blend_epi32::<0xff>(a, b)), [10, 11, 12, 13, 14, 15, 16, 17]);
+    // Bit i of the immediate selects lane i of b
+    assert_eq!(r(_mm256_blend_epi32::<0b1010_1010>(a, b)), [0, 11, 2, 13, 4, 15, 6, 17]);
+    assert_eq!(r(_mm256_blend_epi32::<0b0101_0101>(a, b)), [10, 1, 12, 3, 14, 5, 16, 7]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cvtsd_si64() {
+    let cvt = |f: f64| _mm_cvtsd_si64(_mm_set_sd(f));
+    let cvtt = |f: f64| _mm_cvttsd_si64(_mm_set_sd(f));
+
+    // Round to nearest even unless truncating
+    assert_eq!((cvt(2.5), cvt(-2.5), cvt(3.5)), (2, -2, 4));
+    assert_eq!((cvtt(2.9), cvtt(-2.9)), (2, -2));
+
+    // The largest f64 below 2^63 still fits, 2^63 itself doesn't
+    assert_eq!(cvt(9223372036854774784.0), 9223372036854774784);
+    assert_eq!(cvt(9223372036854775808.0), i64::MIN);
+    assert_eq!(cvt(-9223372036854775808.0), i64::MIN);
+    assert_eq!(cvtt(-1e19), i64::MIN);
+    assert_eq!(cvtt(f64::NAN), i64::MIN);
+
+    // The 32bit variants produce the 32bit indefinite value instead
+    assert_eq!(_mm_cvtsd_si32(_mm_set_sd(2147483647.6)), i32::MIN);
+    assert_eq!(_mm_cvttsd_si32(_mm_set_sd(2147483647.6)), i32::MAX);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_cvtss_si32_si64() {
+    // Only lane 0 is converted
+    let a = _mm_setr_ps(-1.5, f32::NAN, f32::NAN, f32::NAN);
+    assert_eq!((_mm_cvtss_si32(a), _mm_cvttss_si32(a)), (-2, -1));
+    assert_eq!((_mm_cvtss_si64(a), _mm_cvttss_si64(a)), (-2, -1));
+
+    let a = _mm_set_ss(9223371487098961920.0);
+    assert_eq!(_mm_cvtss_si64(a), 9223371487098961920);
+    assert_eq!(_mm_cvtss_si32(a), i32::MIN);
+    assert_eq!(_mm_cvttss_si64(_mm_set_ss(f32::INFINITY)), i64::MIN);
+    assert_eq!(_mm_cvttss_si32(_mm_set_ss(f32::NAN)), i32::MIN);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_cvtss_si32_ties_to_even() {
+    // The default MXCSR rounding mode rounds halfway values to the even integer
+    let cvt = |f: f32| _mm_cvtss_si32(_mm_set_ss(f));
+    assert_eq!([cvt(0.5), cvt(1.5), cvt(2.5), cvt(3.5)], [0, 2, 2, 4]);
+    assert_eq!([cvt(-0.5), cvt(-1.5), cvt(-2.5), cvt(-3.5)], [0, -2, -2, -4]);
+    // Values just off halfway round to the nearest integer rather than to even
+    assert_eq!([cvt(2.5000002), cvt(3.4999998)], [3, 3]);
+    // Above 2^23 every f32 is an integer already
+    assert_eq!(cvt(8388609.0), 8388609);
+
+    let cvt = |f: f32| _mm_cvtss_si64(_mm_set_ss(f));
+    assert_eq!([cvt(0.5), cvt(1.5), cvt(2.5), cvt(-2.5)], [0, 2, 2, -2]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cvtsi64_ss_sd() {
+    // The upper lanes are passed through from the first operand
+    let a = _mm_setr_ps(1.0, 2.0, 3.0, 4.0);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_cvtsi64_ss(a, i64::MAX));
+    assert_eq!(r, [9223372036854775808.0, 2.0, 3.0, 4.0]);
+    let r = std::mem::transmute::<_, [f32; 4]>(_mm_cvtsi32_ss(a, -7));
+    assert_eq!(r, [-7.0, 2.0, 3.0, 4.0]);
+
+    let a = _mm_setr_pd(1.0, 2.0);
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_cvtsi64_sd(a, i64::MIN));
+    assert_eq!(r, [-9223372036854775808.0, 2.0]);
+    let r = std::mem::transmute::<_, [f64; 2]>(_mm_cvtsi64_sd(a, (1 << 53) + 1));
+    assert_eq!(r, [9007199254740992.0, 2.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_cvtsi64_ss_rounding() {
+    let cvt = |i: i64| std::mem::transmute::<_, [f32; 4]>(_mm_cvtsi64_ss(_mm_setzero_ps(), i))[0];
+
+    // Ties round to even
+    assert_eq!(cvt((1 << 24) + 1), 16777216.0);
+    assert_eq!(cvt((1 << 24) + 3), 16777220.0);
+    assert_eq!(cvt(-(1 << 24) - 3), -16777220.0);
+    // The spacing of f32 values around 2^40 is 2^17, so anything above half of it rounds up
+    assert_eq!(cvt((1 << 40) + (1 << 16) + 1), 1099511758848.0);
+    assert_eq!(cvt((1 << 40) + (1 << 16) - 1), 1099511627776.0);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_cvtsi_sd_ss_upper_nan_bits() {
+    // The upper lanes hold signaling NaNs with a payload, which must be passed through bit for bit
+    // rather than being quieted or canonicalized
+    const SNAN_F64: u64 = 0x7ff0_0000_0000_0001;
+    const SNAN_F32: u32 = 0xff80_1234;
+
+    let a = _mm_castsi128_pd(_mm_set_epi64x(SNAN_F64 as i64, 0));
+    let r = std::mem::transmute::<_, [u64; 2]>(_mm_cvtsi32_sd(a, -3));
+    assert_eq!(r, [(-3.0f64).to_bits(), SNAN_F64]);
+    let r = std::mem::transmute::<_, [u64; 2]>(_mm_cvtsi64_sd(a, 1 << 40));
+    assert_eq!(r, [((1u64 << 40) as f64).to_bits(), SNAN_F64]);
+
+    let a = _mm_castsi128_ps(_mm_set1_epi32(SNAN_F32 as i32));
+    let r = std::mem::transmute::<_, [u32; 4]>(_mm_cvtsi32_ss(a, 5));
+    assert_eq!(r, [5.0f32.to_bits(), SNAN_F32, SNAN_F32, SNAN_F32]);
+    let r = std::mem::transmute::<_, [u32; 4]>(_mm_cvtsi64_ss(a, -5));
+    assert_eq!(r, [(-5.0f32).to_bits(), SNAN_F32, SNAN_F32, SNAN_F32]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_round_ps() {
+    let a = _mm_setr_ps(2.5, -2.5, 1.7, -1.7);
+    let r = |r: __m128| std::mem::transmute::<_, [f32; 4]>(r);
+
+    assert_eq!(r(_mm_round_ps::<_MM_FROUND_TO_NEAREST_INT>(a)), [2.0, -2.0, 2.0, -2.0]);
+    assert_eq!(r(_mm_round_ps::<_MM_FROUND_TO_NEG_INF>(a)), [2.0, -3.0, 1.0, -2.0]);
+    assert_eq!(r(_mm_round_ps::<_MM_FROUND_TO_POS_INF>(a)), [3.0, -2.0, 2.0, -1.0]);
+    assert_eq!(r(_mm_round_ps::<_MM_FROUND_TO_ZERO>(a)), [2.0, -2.0, 1.0, -1.0]);
+    assert_eq!(r(_mm_round_ps::<_MM_FROUND_CUR_DIRECTION>(a)), [2.0, -2.0, 2.0, -2.0]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_round_ss_sd() {
+    // Only lane 0 of b is rounded, the upper lanes come from a
+    let a = _mm_setr_ps(10.5, 11.5, 12.5, 13.5);
+    let b = _mm_setr_ps(-1.5, 21.5, 22.5, 23.5);
+    let r = |r: __m128| std::mem::transmute::<_, [f32; 4]>(r);
+
+    assert_eq!(r(_mm_round_ss::<_MM_FROUND_TO_NEAREST_INT>(a, b)), [-2.0, 11.5, 12.5, 13.5]);
+    assert_eq!(r(_mm_round_ss::<_MM_FROUND_TO_NEG_INF>(a, b)), [-2.0, 11.5, 12.5, 13.5]);
+    assert_eq!(r(_mm_round_ss::<_MM_FROUND_TO_POS_INF>(a, b)), [-1.0, 11.5, 12.5, 13.5]);
+    assert_eq!(r(_mm_round_ss::<_MM_FROUND_TO_ZERO>(a, b)), [-1.0, 11.5, 12.5, 13.5]);
+
+    let a = _mm_setr_pd(10.5, 11.5);
+    let b = _mm_setr_pd(0.5, 21.5);
+    let r = |r: __m128d| std::mem::transmute::<_, [f64; 2]>(r);
+
+    assert_eq!(r(_mm_round_sd::<_MM_FROUND_TO_NEAREST_INT>(a, b)), [0.0, 11.5]);
+    assert_eq!(r(_mm_round_sd::<_MM_FROUND_TO_NEG_INF>(a, b)), [0.0, 11.5]);
+    assert_eq!(r(_mm_round_sd::<_MM_FROUND_TO_POS_INF>(a, b)), [1.0, 11.5]);
+    assert_eq!(r(_mm_round_sd::<_MM_FROUND_TO_ZERO>(a, b)), [0.0, 11.5]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_subs_epu8_epu16() {
+    // Unsigned subtraction clamps at 0 instead of wrapping
+    #[rustfmt::skip]
+    let a = _mm_setr_epi8(5, 5, 5, 0, -1, -1, 0x7f, -0x80, 0, 0, 0, 0, 0, 0, 0, 0);
+    #[rustfmt::skip]
+    let b = _mm_setr_epi8(3, 5, 6, 1, 1, -1, -0x80, 0x7f, 0, 0, 0, 0, 0, 0, 0, 0);
+    let r = std::mem::transmute::<_, [u8; 16]>(_mm_subs_epu8(a, b));
+    assert_eq!(r, [2, 0, 0, 0, 254, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+    let a = _mm_setr_epi16(5, 5, 5, 0, -1, -1, 0x7fff, -0x8000);
+    let b = _mm_setr_epi16(3, 5, 6, 1, 1, -1, -0x8000, 0x7fff);
+    let r = std::mem::transmute::<_, [u16; 8]>(_mm_subs_epu16(a, b));
+    assert_eq!(r, [2, 0, 0, 0, 65534, 0, 0, 1]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse4.1")]
+unsafe fn test_mm_extract_ps() {
+    // The lane is returned as its bit pattern rather than converted to an integer
+    let a = _mm_setr_ps(1.0, -1.5, f32::INFINITY, -0.0);
+    assert_eq!(_mm_extract_ps::<0>(a), 0x3f80_0000);
+    assert_eq!(_mm_extract_ps::<1>(a), 0xbfc0_0000_u32 as i32);
+    assert_eq!(_mm_extract_ps::<2>(a), 0x7f80_0000);
+    assert_eq!(_mm_extract_ps::<3>(a), i32::MIN);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_movemask_ps() {
+    let a = _mm_setr_ps(-1.0, 2.0, -0.0, f32::NAN);
+    assert_eq!(_mm_movemask_ps(a), 0b0101);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn test_mm256_movemask_ps_pd() {
+    let a = _mm256_setr_ps(-1.0, 2.0, -0.0, 4.0, 5.0, -6.0, 7.0, f32::NEG_INFINITY);
+    assert_eq!(_mm256_movemask_ps(a), 0b1010_0101);
+    assert_eq!(_mm256_movemask_ps(_mm256_set1_ps(-1.0)), 0xff);
+
+    let a = _mm256_setr_pd(1.0, -2.0, 3.0, -0.0);
+    assert_eq!(_mm256_movemask_pd(a), 0b1010);
+    assert_eq!(_mm256_movemask_pd(_mm256_set1_pd(-1.0)), 0xf);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse")]
+unsafe fn test_mm_comi_ucomi_ss() {
+    // Only lane 0 is compared
+    let one = _mm_setr_ps(1.0, f32::NAN, f32::NAN, f32::NAN);
+    let two = _mm_setr_ps(2.0, 0.0, 0.0, 0.0);
+    let nan = _mm_set1_ps(f32::NAN);
+
+    #[rustfmt::skip]
+    let results = [
+        [_mm_comieq_ss(one, two), _mm_comieq_ss(one, one), _mm_comieq_ss(one, nan)],
+        [_mm_comilt_ss(one, two), _mm_comilt_ss(two, one), _mm_comilt_ss(nan, one)],
+        [_mm_comile_ss(one, one), _mm_comile_ss(two, one), _mm_comile_ss(one, nan)],
+        [_mm_comigt_ss(two, one), _mm_comigt_ss(one, two), _mm_comigt_ss(nan, one)],
+        [_mm_comige_ss(one, one), _mm_comige_ss(one, two), _mm_comige_ss(one, nan)],
+        [_mm_comineq_ss(one, two), _mm_comineq_ss(one, one), _mm_comineq_ss(nan, nan)],
+        [_mm_ucomieq_ss(one, two), _mm_ucomieq_ss(one, one), _mm_ucomieq_ss(one, nan)],
+        [_mm_ucomilt_ss(one, two), _mm_ucomilt_ss(two, one), _mm_ucomilt_ss(nan, one)],
+        [_mm_ucomile_ss(one, one), _mm_ucomile_ss(two, one), _mm_ucomile_ss(one, nan)],
+        [_mm_ucomigt_ss(two, one), _mm_ucomigt_ss(one, two), _mm_ucomigt_ss(nan, one)],
+        [_mm_ucomige_ss(one, one), _mm_ucomige_ss(one, two), _mm_ucomige_ss(one, nan)],
+        [_mm_ucomineq_ss(one, two), _mm_ucomineq_ss(one, one), _mm_ucomineq_ss(nan, nan)],
+    ];
+    // The last column has a NaN operand, which is only true for neq
+    #[rustfmt::skip]
+    let expected = [
+        [0, 1, 0], [1, 0, 0], [1, 0, 0], [1, 0, 0], [1, 0, 0], [1, 0, 1],
+        [0, 1, 0], [1, 0, 0], [1, 0, 0], [1, 0, 0], [1, 0, 0], [1, 0, 1],
+    ];
+    assert_eq!(results, expected);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_comi_ucomi_sd() {
+    let one = _mm_setr_pd(1.0, f64::NAN);
+    let two = _mm_setr_pd(2.0, 0.0);
+    let nan = _mm_set1_pd(f64::NAN);
+
+    #[rustfmt::skip]
+    let results = [
+        [_mm_comieq_sd(one, two), _mm_comieq_sd(one, one), _mm_comieq_sd(one, nan)],
+        [_mm_comilt_sd(one, two), _mm_comilt_sd(two, one), _mm_comilt_sd(nan, one)],
+        [_mm_comile_sd(one, one), _mm_comile_sd(two, one), _mm_comile_sd(one, nan)],
+        [_mm_comigt_sd(two, one), _mm_comigt_sd(one, two), _mm_comigt_sd(nan, one)],
+        [_mm_comige_sd(one, one), _mm_comige_sd(one, two), _mm_comige_sd(one, nan)],
+        [_mm_comineq_sd(one, two), _mm_comineq_sd(one, one), _mm_comineq_sd(nan, nan)],
+        [_mm_ucomieq_sd(one, two), _mm_ucomieq_sd(one, one), _mm_ucomieq_sd(one, nan)],
+        [_mm_ucomilt_sd(one, two), _mm_ucomilt_sd(two, one), _mm_ucomilt_sd(nan, one)],
+        [_mm_ucomile_sd(one, one), _mm_ucomile_sd(two, one), _mm_ucomile_sd(one, nan)],
+        [_mm_ucomigt_sd(two, one), _mm_ucomigt_sd(one, two), _mm_ucomigt_sd(nan, one)],
+        [_mm_ucomige_sd(one, one), _mm_ucomige_sd(one, two), _mm_ucomige_sd(one, nan)],
+        [_mm_ucomineq_sd(one, two), _mm_ucomineq_sd(one, one), _mm_ucomineq_sd(nan, nan)],
+    ];
+    #[rustfmt::skip]
+    let expected = [
+        [0, 1, 0], [1, 0, 0], [1, 0, 0], [1, 0, 0], [1, 0, 0], [1, 0, 1],
+        [0, 1, 0], [1, 0, 0], [1, 0, 0], [1, 0, 0], [1, 0, 0], [1, 0, 1],
+    ];
+    assert_eq!(results, expected);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "ssse3")]
+unsafe fn test_mm_mulhrs_epi16() {
+    // The products are 0x3fff, 0x4000, 0xbfff, -0x4000, -0x4001, -0x3fff (0x4000 is the rounding
+    // bias), 0x3fff0001 and 0x40000000 (which wraps to i16::MIN).
+    let a = _mm_setr_epi16(0x3fff, 0x4000, 0x5fff, -0x4000, -0x4001, -0x3fff, 0x7fff, i16::MIN);
+    let b = _mm_setr_epi16(1, 1, 2, 1, 1, 1, 0x7fff, i16::MIN);
+    let r: [i16; 8] = std::mem::transmute(_mm_mulhrs_epi16(a, b));
+    assert_eq!(r, [0, 1, 1, 0, -1, 0, 0x7ffe, i16::MIN]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_mulhrs_epi16() {
+    let a = _mm256_setr_epi16(
+        0x3fff,
+        0x4000,
+        0x5fff,
+        -0x4000,
+        -0x4001,
+        -0x3fff,
+        0x7fff,
+        i16::MIN,
+        0x3fff,
+        0x4000,
+        0x5fff,
+        -0x4000,
+        -0x4001,
+        -0x3fff,
+        0x7fff,
+        i16::MIN,
+    );
+    let b = _mm256_setr_epi16(1, 1, 2, 1, 1, 1, 0x7fff, i16::MIN, 1, 1, 2, 1, 1, 1, 0x7fff, -1);
+    let r: [i16; 16] = std::mem::transmute(_mm256_mulhrs_epi16(a, b));
+    #[rustfmt::skip]
+    let e = [
+        0, 1, 1, 0, -1, 0, 0x7ffe, i16::MIN,
+        0, 1, 1, 0, -1, 0, 0x7ffe, 1,
+    ];
+    assert_eq!(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_madd_epi16() {
+    // Result lane i is a[2i] * b[2i] + a[2i+1] * b[2i+1]
+    let a = _mm_setr_epi16(1, 2, 3, 4, -5, 6, i16::MIN, i16::MIN);
+    let b = _mm_setr_epi16(10, 100, 1000, 10000, 7, 8, 1, 1);
+    let r: [i32; 4] = std::mem::transmute(_mm_madd_epi16(a, b));
+    assert_eq!(r, [210, 43000, 13, -0x10000]);
+
+    // The only case which overflows wraps around
+    let a = _mm_set1_epi16(i16::MIN);
+    let r: [i32; 4] = std::mem::transmute(_mm_madd_epi16(a, a));
+    assert_eq!(r, [i32::MIN; 4]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+unsafe fn test_mm_madd_epi16_sign_extension() {
+    // Zero extending the lanes would give 0xfffe * 3 + 0xffff * 0xffff = 0xfffd_0000 for the
+    // first lane rather than -2 * 3 + -1 * -1 = -5
+    let a = _mm_setr_epi16(-2, -1, -32768, 1, 5, -7, -1, -1);
+    let b = _mm_setr_epi16(3, -1, 2, -1, -4, -3, 1, 1);
+    let r: [i32; 4] = std::mem::transmute(_mm_madd_epi16(a, b));
+    assert_eq!(r, [-5, -65537, 1, -2]);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn test_mm256_maddubs_epi16() {
+    // The lanes of a are unsigned and those of b signed, the sum saturates
+    #[rustfmt::skip]
+    let a = _mm256_setr_epi8(
+        1, 2, 3, 4, -1, -1, -1, -1, -1, 0, 0, 0, 0, 0, 0, 0,
+        5, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+    );
+    #[rustfmt::skip]
+    let b = _mm256_setr_epi8(
+        10, 20, -30, 40, 127, 127, -128, -128, -1, 0, 0, 0, 0, 0, 0, 0,
+        1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -7,
+    );
+    let r: [i16; 16] = std::mem::transmute(_mm256_maddubs_epi16(a, b));
+    #[rustfmt::skip]
+    let e = [
+        50, 70, i16::MAX, i16::MIN, -255, 0, 0, 0,
+        11, 0, 0, 0, 0, 0, 0, -7,
+    ];
+    assert_eq!(r, e);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi1")]
+unsafe fn test_andn_u32() {
+    // The first operand is the inverted one: !a & b, not a & !b
+    assert_eq!(_andn_u32(black_box(0b1100), black_box(0b1010)), 0b0010);
+    assert_eq!(_andn_u32(black_box(0), black_box(u32::MAX)), u32::MAX);
+    assert_eq!(_andn_u32(black_box(u32::MAX), black_box(0)), 0);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi1")]
+unsafe fn test_andn_u64() {
+    let a = black_box(0xffff_0000_ffff_0000);
+    let b = black_box(0xff00_ff00_ff00_ff00);
+    assert_eq!(_andn_u64(a, b), 0x0000_ff00_0000_ff00);
+    assert_eq!(_andn_u64(b, a), 0x00ff_0000_00ff_0000);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi1")]
+unsafe fn test_bls_u32() {
+    assert_eq!(_blsi_u32(black_box(0b1011_0100)), 0b0000_0100);
+    assert_eq!(_blsr_u32(black_box(0b1011_0100)), 0b1011_0000);
+    assert_eq!(_blsmsk_u32(black_box(0b1011_0100)), 0b0000_0111);
+
+    assert_eq!(_blsi_u32(black_box(0)), 0);
+    assert_eq!(_blsr_u32(black_box(0)), 0);
+    assert_eq!(_blsmsk_u32(black_box(0)), u32::MAX);
+
+    assert_eq!(_blsi_u32(black_box(1 << 31)), 1 << 31);
+    assert_eq!(_blsr_u32(black_box(1 << 31)), 0);
+    assert_eq!(_blsmsk_u32(black_box(1 << 31)), u32::MAX);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi1")]
+unsafe fn test_bls_u64() {
+    assert_eq!(_blsi_u64(black_box(0xff00_0000_0000)), 0x0100_0000_0000);
+    assert_eq!(_blsr_u64(black_box(0xff00_0000_0000)), 0xfe00_0000_0000);
+    assert_eq!(_blsmsk_u64(black_box(0xff00_0000_0000)), 0x01ff_ffff_ffff);
+
+    assert_eq!(_blsi_u64(black_box(0)), 0);
+    assert_eq!(_blsr_u64(black_box(0)), 0);
+    assert_eq!(_blsmsk_u64(black_box(0)), u64::MAX);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "xsave")]
+unsafe fn test_xsave_xrstor() {
+    // x87 and SSE state, which includes MXCSR
+    const MASK: u64 = 0b11;
+    const MXCSR_OFFSET: usize = 24;
+
+    #[repr(align(64))]
+    struct XsaveArea([u8; 1024]);
+
+    fn mxcsr(area: &XsaveArea) -> u32 {
+        u32::from_le_bytes(area.0[MXCSR_OFFSET..MXCSR_OFFSET + 4].try_into().unwrap())
+    }
+
+    // Heap allocate to get the 64 byte alignment xsave requires
+    let mut orig = Box::new(XsaveArea([0; 1024]));
+    _xsave(orig.0.as_mut_ptr(), MASK);
+
+    // Restore a state with round toward zero and read it back
+    let mut modified = Box::new(XsaveArea(orig.0));
+    let new_mxcsr = mxcsr(&orig) | 0x6000;
+    modified.0[MXCSR_OFFSET..MXCSR_OFFSET + 4].copy_from_slice(&new_mxcsr.to_le_bytes());
+    _xrstor(modified.0.as_ptr(), MASK);
+
+    let mut saved = Box::new(XsaveArea([0; 1024]));
+    _xsave(saved.0.as_mut_ptr(), MASK);
+    _xrstor(orig.0.as_ptr(), MASK);
+
+    assert_eq!(mxcsr(&saved), new_mxcsr);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "fxsr")]
+unsafe fn test_fxsave_fxrstor() {
+    const FCW_OFFSET: usize = 0;
+    const MXCSR_OFFSET: usize = 24;
+
+    #[repr(align(16))]
+    struct FxsaveArea([u8; 512]);
+
+    fn read_u32(area: &FxsaveArea, offset: usize) -> u32 {
+        u32::from_le_bytes(area.0[offset..offset + 4].try_into().unwrap())
+    }
+
+    let mut orig = Box::new(FxsaveArea([0; 512]));
+    _fxsave64(orig.0.as_mut_ptr());
+
+    // Restore a state with round toward zero for both SSE and x87 and read it back
+    let mut modified = Box::new(FxsaveArea(orig.0));
+    let new_mxcsr = read_u32(&orig, MXCSR_OFFSET) | 0x6000;
+    modified.0[MXCSR_OFFSET..MXCSR_OFFSET + 4].copy_from_slice(&new_mxcsr.to_le_bytes());
+    let new_fcw = u16::from_le_bytes([orig.0[FCW_OFFSET], orig.0[FCW_OFFSET + 1]]) | 0x0c00;
+    modified.0[FCW_OFFSET..FCW_OFFSET + 2].copy_from_slice(&new_fcw.to_le_bytes());
+    _fxrstor64(modified.0.as_ptr());
+
+    let mut saved = Box::new(FxsaveArea([0; 512]));
+    _fxsave(saved.0.as_mut_ptr());
+    _fxrstor(orig.0.as_ptr());
+
+    assert_eq!(read_u32(&saved, MXCSR_OFFSET), new_mxcsr);
+    assert_eq!(u16::from_le_bytes([saved.0[FCW_OFFSET], saved.0[FCW_OFFSET + 1]]), new_fcw);
+}
+
+fn test_checked_mul() {
+    let u: Option<u8> = u8::from_str_radix("1000", 10).ok();
+    assert_eq!(u, None);
+
+    assert_eq!(1u8.checked_mul(255u8), Some(255u8));
+    assert_eq!(255u8.checked_mul(255u8), None);
+    assert_eq!(1i8.checked_mul(127i8), Some(127i8));
+    assert_eq!(127i8.checked_mul(127i8), None);
+    assert_eq!((-1i8).checked_mul(-127i8), Some(127i8));
+    assert_eq!(1i8.checked_mul(-128i8), Some(-128i8));
+    assert_eq!((-128i8).checked_mul(-128i8), None);
+
+    assert_eq!(1u64.checked_mul(u64::MAX), Some(u64::MAX));
+    assert_eq!(u64::MAX.checked_mul(u64::MAX), None);
+    assert_eq!(1i64.checked_mul(i64::MAX), Some(i64::MAX));
+    assert_eq!(i64::MAX.checked_mul(i64::MAX), None);
+    assert_eq!((-1i64).checked_mul(i64::MIN + 1), Some(i64::MAX));
+    assert_eq!(1i64.checked_mul(i64::MIN), Some(i64::MIN));
+    assert_eq!(i64::MIN.checked_mul(i64::MIN), None);
+}
+
+fn test_llvm_bswap() {
+    #[allow(improper_ctypes)]
+    extern "C" {
+        #[link_name = "llvm.bswap.i16"]
+        fn bswap_i16(x: u16) -> u16;
+        #[link_name = "llvm.bswap.i32"]
+        fn bswap_i32(x: u32) -> u32;
+        #[link_name = "llvm.bswap.i64"]
+        fn bswap_i64(x: u64) -> u64;
+        #[link_name = "llvm.bswap.i128"]
+        fn bswap_i128(x: u128) -> u128;
+    }
+
+    unsafe {
+        assert_eq!(bswap_i16(black_box(0x0102)), 0x0201);
+        assert_eq!(bswap_i32(black_box(0x0102_0304)), 0x0403_0201);
+        assert_eq!(bswap_i64(black_box(0x0102_0304_0506_0708)), 0x0807_0605_0403_0201);
+        // Both i64 halves are byte swapped and exchanged
+        assert_eq!(
+            bswap_i128(black_box(0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10)),
+            0x100f_0e0d_0c0b_0a09_0807_0605_0403_0201,
+        );
+    }
+
+    assert_eq!(black_box(0x0102u16).swap_bytes(), 0x0201);
+    assert_eq!(
+        black_box(0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10u128).swap_bytes(),
+        0x100f_0e0d_0c0b_0a09_0807_0605_0403_0201
+    );
+}
+
+fn test_ptr_mask() {
+    extern "C" {
+        #[link_name = "llvm.ptrmask.p0.i64"]
+        fn ptrmask(ptr: *const u8, mask: u64) -> *const u8;
+    }
+
+    let ptr = black_box(0x1234_5678 as *const u8);
+    assert_eq!(ptr.mask(!15), 0x1234_5670 as *const u8);
+    assert_eq!(ptr.mask(!0xfff), 0x1234_5000 as *const u8);
+
+    #[cfg(target_pointer_width = "64")]
+    unsafe {
+        assert_eq!(ptrmask(ptr, !63), 0x1234_5640 as *const u8);
+    }
+}
+
+fn test_contended_atomics() {
+    use std::sync::atomic::{AtomicI32, AtomicU64, AtomicU8, Ordering};
+
+    const THREADS: u64 = 4;
+    const ITERATIONS: u64 = 1000;
+
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    static MAX: AtomicI32 = AtomicI32::new(i32::MIN);
+    static NAND: AtomicU8 = AtomicU8::new(0);
+
+    // Atomic rmw operations without a native instruction are lowered to compare-exchange loops
+    let threads = (0..THREADS)
+        .map(|thread| {
+            std::thread::spawn(move || {
+                for i in 0..ITERATIONS {
+                    COUNTER
+                        .fetch_update(Ordering::AcqRel, Ordering::Acquire, |x| Some(x + 1))
+                        .unwrap();
+                    MAX.fetch_max((thread * ITERATIONS + i) as i32, Ordering::AcqRel);
+                    NAND.fetch_nand(0xff, Ordering::AcqRel);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(COUNTER.load(Ordering::Relaxed), THREADS * ITERATIONS);
+    assert_eq!(MAX.load(Ordering::Relaxed), (THREADS * ITERATIONS - 1) as i32);
+    // Every nand with 0xff flips all bits and there is an even number of them
+    assert_eq!(NAND.load(Ordering::Relaxed), 0);
 }
 
 #[derive(PartialEq)]
@@ -0,0 +1,106 @@
+// Check intrinsics both with and without their target feature enabled for the whole crate. This
+// test is built once with `-Ctarget-feature=+sse4.2,+pclmulqdq` and once without. Both the
+// intrinsic based implementation and the portable fallback a program would use when the feature
+// isn't detected at runtime are checked against known values.
+
+fn main() {
+    #[cfg(target_arch = "x86_64")]
+    x86_64::run();
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86_64 {
+    use std::arch::x86_64::*;
+
+    fn crc32c_fallback(mut crc: u32, data: &[u8]) -> u32 {
+        for &byte in data {
+            crc ^= u32::from(byte);
+            for _ in 0..8 {
+                crc = (crc >> 1) ^ (0x82f6_3b78 & (crc & 1).wrapping_neg());
+            }
+        }
+        crc
+    }
+
+    #[target_feature(enable = "sse4.2")]
+    unsafe fn crc32c_native(mut crc: u32, mut data: &[u8]) -> u32 {
+        // Use every operand width of the crc32 instruction
+        while let &[a, b, c, d, e, f, g, h, ref rest @ ..] = data {
+            crc = _mm_crc32_u64(crc.into(), u64::from_le_bytes([a, b, c, d, e, f, g, h])) as u32;
+            data = rest;
+        }
+        if let &[a, b, c, d, ref rest @ ..] = data {
+            crc = _mm_crc32_u32(crc, u32::from_le_bytes([a, b, c, d]));
+            data = rest;
+        }
+        if let &[a, b, ref rest @ ..] = data {
+            crc = _mm_crc32_u16(crc, u16::from_le_bytes([a, b]));
+            data = rest;
+        }
+        for &byte in data {
+            crc = _mm_crc32_u8(crc, byte);
+        }
+        crc
+    }
+
+    fn crc32c(data: &[u8]) -> u32 {
+        let crc = if is_x86_feature_detected!("sse4.2") {
+            unsafe { crc32c_native(!0, data) }
+        } else {
+            crc32c_fallback(!0, data)
+        };
+        !crc
+    }
+
+    fn clmul_fallback(a: u64, b: u64) -> u128 {
+        let mut res = 0;
+        for bit in 0..64 {
+            if b & (1 << bit) != 0 {
+                res ^= u128::from(a) << bit;
+            }
+        }
+        res
+    }
+
+    #[target_feature(enable = "pclmulqdq")]
+    unsafe fn clmul_native(a: u64, b: u64) -> u128 {
+        // Place the operands in different halves to check the selection by the immediate
+        let a = _mm_set_epi64x(a as i64, 0);
+        let b = _mm_set_epi64x(0, b as i64);
+        std::mem::transmute(_mm_clmulepi64_si128::<0x01>(a, b))
+    }
+
+    fn clmul(a: u64, b: u64) -> u128 {
+        if is_x86_feature_detected!("pclmulqdq") {
+            unsafe { clmul_native(a, b) }
+        } else {
+            clmul_fallback(a, b)
+        }
+    }
+
+    pub(crate) fn run() {
+        const CRC_INPUT: &[u8] = b"123456789 and some more bytes";
+        const CRC_CHECK: u32 = 0xe306_9283;
+
+        assert_eq!(!crc32c_fallback(!0, b"123456789"), CRC_CHECK);
+        assert_eq!(crc32c(b"123456789"), CRC_CHECK);
+        assert_eq!(crc32c(CRC_INPUT), !crc32c_fallback(!0, CRC_INPUT));
+        if is_x86_feature_detected!("sse4.2") {
+            assert_eq!(!unsafe { crc32c_native(!0, b"123456789") }, CRC_CHECK);
+        }
+
+        assert_eq!(clmul_fallback(0b11, 0b11), 0b101);
+        for (a, b, expected) in [
+            (0b11, 0b11, 0b101),
+            (u64::MAX, 1, u128::from(u64::MAX)),
+            (1 << 63, 1 << 63, 1 << 126),
+            (0x8765_4321_0fed_cba9, 0x1234_5678_9abc_def0, 0),
+        ] {
+            let expected = if expected == 0 { clmul_fallback(a, b) } else { expected };
+            assert_eq!(clmul(a, b), expected, "a={a:#x} b={b:#x}");
+            if is_x86_feature_detected!("pclmulqdq") {
+                assert_eq!(unsafe { clmul_native(a, b) }, expected, "a={a:#x} b={b:#x}");
+            }
+        }
+    }
+}
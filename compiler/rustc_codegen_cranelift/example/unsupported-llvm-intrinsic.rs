@@ -0,0 +1,14 @@
+// Test that reaching the trap emitted for an unsupported llvm intrinsic prints the name of the
+// intrinsic even when stdout is redirected.
+
+#![feature(link_llvm_intrinsics)]
+#![allow(improper_ctypes)]
+
+extern "C" {
+    #[link_name = "llvm.cg-clif.unsupported-for-test"]
+    fn unsupported(a: u32) -> u32;
+}
+
+fn main() {
+    println!("{}", unsafe { unsupported(std::hint::black_box(1)) });
+}
@@ -0,0 +1,33 @@
+// Smoke test for the cache line flush and writeback intrinsics which are implemented using inline
+// asm. Their effect isn't observable, so this only checks that they run without faulting.
+
+#![feature(link_llvm_intrinsics)]
+
+#[cfg(target_arch = "x86_64")]
+extern "C" {
+    #[link_name = "llvm.x86.clflushopt"]
+    fn clflushopt(p: *const u8);
+    #[link_name = "llvm.x86.clwb"]
+    fn clwb(p: *const u8);
+}
+
+fn main() {
+    #[cfg(target_arch = "x86_64")]
+    {
+        let data = Box::new([1u8; 64]);
+
+        unsafe { std::arch::x86_64::_mm_clflush(data.as_ptr()) };
+
+        // cpuid is emulated by cg_clif and doesn't report these features, so check /proc/cpuinfo
+        let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+        let has_flag = |name| cpuinfo.split_whitespace().any(|flag| flag == name);
+        if has_flag("clflushopt") {
+            unsafe { clflushopt(data.as_ptr()) };
+        }
+        if has_flag("clwb") {
+            unsafe { clwb(data.as_ptr()) };
+        }
+
+        assert_eq!(*data, [1u8; 64]);
+    }
+}
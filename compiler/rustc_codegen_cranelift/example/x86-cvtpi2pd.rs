@@ -0,0 +1,27 @@
+// `_mm_cvtpi32_pd` isn't available in core::arch anymore as it uses MMX registers. LLVM would
+// reject this declaration as the operand isn't of type x86_mmx, so this test is cg_clif only.
+
+#![feature(link_llvm_intrinsics, repr_simd, simd_ffi)]
+#![allow(improper_ctypes, non_camel_case_types)]
+
+#[repr(simd)]
+#[derive(Copy, Clone)]
+struct i32x2(i32, i32);
+
+#[repr(simd)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct f64x2(f64, f64);
+
+#[cfg(target_arch = "x86_64")]
+extern "C" {
+    #[link_name = "llvm.x86.sse.cvtpi2pd"]
+    fn cvtpi2pd(a: i32x2) -> f64x2;
+}
+
+fn main() {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        assert_eq!(cvtpi2pd(std::hint::black_box(i32x2(-7, 3))), f64x2(-7.0, 3.0));
+        assert_eq!(cvtpi2pd(std::hint::black_box(i32x2(i32::MIN, -1))), f64x2(-2147483648.0, -1.0));
+    }
+}
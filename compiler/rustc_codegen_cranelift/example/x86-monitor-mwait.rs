@@ -0,0 +1,19 @@
+// Test that the monitor and mwait intrinsics are lowered to the native instructions rather than
+// a trap. The instructions are usually only allowed in ring 0, so this is only compiled.
+
+#![feature(link_llvm_intrinsics)]
+
+#[cfg(target_arch = "x86_64")]
+extern "C" {
+    #[link_name = "llvm.x86.sse3.monitor"]
+    fn monitor(addr: *const u8, extensions: u32, hints: u32);
+    #[link_name = "llvm.x86.sse3.mwait"]
+    fn mwait(extensions: u32, hints: u32);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse3")]
+pub unsafe fn wait_for_write(addr: *const u8) {
+    monitor(addr, 0, 0);
+    mwait(0, 0);
+}
@@ -0,0 +1,52 @@
+// Test the rdpid and rdpmc intrinsics which are implemented using inline asm.
+
+#![feature(link_llvm_intrinsics)]
+
+#[cfg(target_arch = "x86_64")]
+extern "C" {
+    #[link_name = "llvm.x86.rdpid"]
+    fn rdpid() -> u32;
+    #[link_name = "llvm.x86.rdpmc"]
+    fn rdpmc(counter: i32) -> i64;
+}
+
+#[cfg(target_os = "linux")]
+extern "C" {
+    fn sched_getcpu() -> i32;
+}
+
+/// Linux stores the number of the cpu in the low 12 bits of the processor id returned by rdpid.
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+fn check_rdpid_cpu() {
+    // The thread may migrate to another cpu between the calls, so retry a couple of times
+    for _ in 0..100 {
+        let before = unsafe { sched_getcpu() };
+        let processor_id = unsafe { rdpid() };
+        let after = unsafe { sched_getcpu() };
+        if before == after {
+            assert_eq!(processor_id & 0xfff, before as u32);
+            return;
+        }
+    }
+}
+
+fn main() {
+    #[cfg(target_arch = "x86_64")]
+    {
+        // cpuid is emulated by cg_clif and doesn't report rdpid support, so check /proc/cpuinfo
+        let has_rdpid = std::fs::read_to_string("/proc/cpuinfo")
+            .map_or(false, |cpuinfo| cpuinfo.split_whitespace().any(|flag| flag == "rdpid"));
+        if has_rdpid {
+            let processor_id = unsafe { rdpid() };
+            println!("rdpid: {}", processor_id);
+            #[cfg(target_os = "linux")]
+            check_rdpid_cpu();
+        }
+
+        // Reading performance counters faults unless the OS allows it, so only check that rdpmc
+        // compiles
+        if std::hint::black_box(false) {
+            println!("rdpmc: {}", unsafe { rdpmc(0) });
+        }
+    }
+}
@@ -119,6 +119,18 @@ pub(crate) fn codegen_fn<'tcx>(
         );
     }
 
+    if let Some(dump_fn) = &cx.dump_fn {
+        let def_id = instance.def_id();
+        let def_path = format!(
+            "{}{}",
+            tcx.crate_name(def_id.krate),
+            tcx.def_path(def_id).to_string_no_crate_verbose()
+        );
+        if *dump_fn == symbol_name || *dump_fn == def_path {
+            crate::pretty_clif::print_clif(&symbol_name, &func, &clif_comments);
+        }
+    }
+
     // Verify function
     verify_func(tcx, &clif_comments, &func);
 
@@ -57,6 +57,13 @@ pub struct BackendConfig {
     /// Defaults to true when the `CG_CLIF_DISABLE_INCR_CACHE` env var is set to 1 or false
     /// otherwise. Can be set using `-Cllvm-args=disable_incr_cache=...`.
     pub disable_incr_cache: bool,
+
+    /// Print the Cranelift ir of functions with this path (like `krate::module::function`) or
+    /// symbol name to stderr after they have been codegened and before they are optimized. Useful
+    /// to inspect what an intrinsic is lowered to.
+    ///
+    /// Defaults to the value of `CG_CLIF_DUMP_FN`. Can be set using `-Cllvm-args=dump_fn=...`.
+    pub dump_fn: Option<String>,
 }
 
 impl Default for BackendConfig {
@@ -69,6 +76,7 @@ fn default() -> Self {
             },
             enable_verifier: cfg!(debug_assertions) || bool_env_var("CG_CLIF_ENABLE_VERIFIER"),
             disable_incr_cache: bool_env_var("CG_CLIF_DISABLE_INCR_CACHE"),
+            dump_fn: env::var("CG_CLIF_DUMP_FN").ok(),
         }
     }
 }
@@ -87,6 +95,7 @@ fn parse_bool(name: &str, value: &str) -> Result<bool, String> {
                     "mode" => config.codegen_mode = value.parse()?,
                     "enable_verifier" => config.enable_verifier = parse_bool(name, value)?,
                     "disable_incr_cache" => config.disable_incr_cache = parse_bool(name, value)?,
+                    "dump_fn" => config.dump_fn = Some(value.to_owned()),
                     _ => return Err(format!("Unknown option `{}`", name)),
                 }
             } else {
@@ -5,6 +5,7 @@
 use rustc_middle::mir::interpret::{
     read_target_uint, AllocId, ConstAllocation, ConstValue, ErrorHandled, GlobalAlloc, Scalar,
 };
+use rustc_middle::ty::ScalarInt;
 
 use cranelift_module::*;
 
@@ -494,12 +495,12 @@ pub(crate) fn mir_operand_get_const_val<'tcx>(
                         StatementKind::Assign(local_and_rvalue) if &local_and_rvalue.0 == place => {
                             match &local_and_rvalue.1 {
                                 Rvalue::Cast(
-                                    CastKind::IntToInt
+                                    cast_kind @ (CastKind::IntToInt
                                     | CastKind::FloatToFloat
                                     | CastKind::FloatToInt
                                     | CastKind::IntToFloat
                                     | CastKind::FnPtrToPtr
-                                    | CastKind::PtrToPtr,
+                                    | CastKind::PtrToPtr),
                                     operand,
                                     ty,
                                 ) => {
@@ -510,12 +511,23 @@ pub(crate) fn mir_operand_get_const_val<'tcx>(
                                         return None;
                                     }
                                     let const_val = mir_operand_get_const_val(fx, operand)?;
-                                    if fx.layout_of(*ty).size
-                                        != const_val.try_to_scalar_int()?.size()
+                                    let const_int = const_val.try_to_scalar_int()?;
+                                    let size = fx.layout_of(*ty).size;
+                                    if size == const_int.size() {
+                                        computed_const_val = Some(const_val);
+                                    } else if *cast_kind == CastKind::IntToInt
+                                        && size < const_int.size()
                                     {
+                                        // Truncating casts like `IMM8 as u8` only drop the high
+                                        // bits.
+                                        let bits = const_int.assert_bits(const_int.size());
+                                        let truncated =
+                                            ScalarInt::try_from_uint(size.truncate(bits), size)?;
+                                        computed_const_val =
+                                            Some(ConstValue::Scalar(Scalar::Int(truncated)));
+                                    } else {
                                         return None;
                                     }
-                                    computed_const_val = Some(const_val);
                                 }
                                 Rvalue::Use(operand) => {
                                     computed_const_val = mir_operand_get_const_val(fx, operand)
@@ -12,6 +12,7 @@ pub(crate) fn codegen_llvm_intrinsic_call<'tcx>(
     args: &[mir::Operand<'tcx>],
     ret: CPlace<'tcx>,
     target: Option<BasicBlock>,
+    span: Span,
 ) {
     if intrinsic.starts_with("llvm.aarch64") {
         return llvm_aarch64::codegen_aarch64_llvm_intrinsic_call(
@@ -19,7 +20,9 @@ pub(crate) fn codegen_llvm_intrinsic_call<'tcx>(
         );
     }
     if intrinsic.starts_with("llvm.x86") {
-        return llvm_x86::codegen_x86_llvm_intrinsic_call(fx, intrinsic, substs, args, ret, target);
+        return llvm_x86::codegen_x86_llvm_intrinsic_call(
+            fx, intrinsic, substs, args, ret, target, span,
+        );
     }
 
     match intrinsic {
@@ -39,11 +42,46 @@ pub(crate) fn codegen_llvm_intrinsic_call<'tcx>(
             });
         }
 
+        _ if intrinsic.starts_with("llvm.ptrmask.") => {
+            intrinsic_args!(fx, args => (ptr, mask); intrinsic);
+
+            let pointer_size = fx.tcx.data_layout.pointer_size;
+            if ptr.layout().size != pointer_size || mask.layout().size != pointer_size {
+                fx.tcx.sess.span_err(
+                    span,
+                    format!(
+                        "invalid call to `{intrinsic}`: expected the pointer and the mask to be {} bits wide, found `{}` and `{}`",
+                        pointer_size.bits(),
+                        ptr.layout().ty,
+                        mask.layout().ty,
+                    ),
+                );
+                // Prevent verifier error
+                fx.bcx.ins().trap(TrapCode::UnreachableCodeReached);
+                return;
+            }
+
+            // Pointers are plain integers in Cranelift, so there is no provenance to preserve
+            let ptr = ptr.load_scalar(fx);
+            let mask = mask.load_scalar(fx);
+            let res = fx.bcx.ins().band(ptr, mask);
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+        }
+
+        _ if intrinsic.starts_with("llvm.bswap.i") => {
+            // Cranelift's bswap supports all integer types larger than i8, including i128
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let val = a.load_scalar(fx);
+            let res = CValue::by_val(fx.bcx.ins().bswap(val), a.layout());
+            ret.write_cvalue(fx, res);
+        }
+
         _ => {
             fx.tcx
                 .sess
                 .warn(format!("unsupported llvm intrinsic {}; replacing with trap", intrinsic));
-            crate::trap::trap_unimplemented(fx, intrinsic);
+            crate::trap::trap_unsupported_intrinsic(fx, intrinsic);
             return;
         }
     }
@@ -211,7 +211,7 @@ pub(crate) fn codegen_aarch64_llvm_intrinsic_call<'tcx>(
                 "unsupported AArch64 llvm intrinsic {}; replacing with trap",
                 intrinsic
             ));
-            crate::trap::trap_unimplemented(fx, intrinsic);
+            crate::trap::trap_unsupported_intrinsic(fx, intrinsic);
             return;
         }
     }
@@ -1,9 +1,12 @@
 //! Emulate x86 LLVM intrinsics
 
+use crate::inline_asm::{codegen_inline_asm_inner, CInlineAsmOperand};
 use crate::intrinsics::*;
 use crate::prelude::*;
 
+use rustc_ast::ast::{InlineAsmOptions, InlineAsmTemplatePiece};
 use rustc_middle::ty::subst::SubstsRef;
+use rustc_target::asm::*;
 
 pub(crate) fn codegen_x86_llvm_intrinsic_call<'tcx>(
     fx: &mut FunctionCx<'_, '_, 'tcx>,
@@ -12,21 +15,31 @@ pub(crate) fn codegen_x86_llvm_intrinsic_call<'tcx>(
     args: &[mir::Operand<'tcx>],
     ret: CPlace<'tcx>,
     target: Option<BasicBlock>,
+    span: Span,
 ) {
     match intrinsic {
         "llvm.x86.sse2.pause" | "llvm.aarch64.isb" => {
             // Spin loop hint
         }
 
-        // Used by `_mm_movemask_epi8` and `_mm256_movemask_epi8`
-        "llvm.x86.sse2.pmovmskb.128" | "llvm.x86.avx2.pmovmskb" | "llvm.x86.sse2.movmsk.pd" => {
+        // Used by `_mm_movemask_epi8`, `_mm256_movemask_epi8` and the float `movemask` variants
+        "llvm.x86.sse2.pmovmskb.128"
+        | "llvm.x86.avx2.pmovmskb"
+        | "llvm.x86.sse.movmsk.ps"
+        | "llvm.x86.sse2.movmsk.pd"
+        | "llvm.x86.avx.movmsk.ps.256"
+        | "llvm.x86.avx.movmsk.pd.256" => {
             intrinsic_args!(fx, args => (a); intrinsic);
 
             let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
             let lane_ty = fx.clif_type(lane_ty).unwrap();
-            assert!(lane_count <= 32);
 
-            let mut res = fx.bcx.ins().iconst(types::I32, 0);
+            // Produce the return type of the declaration rather than hardcoding i32
+            let res_ty = fx.clif_type(ret.layout().ty).unwrap();
+            assert!(res_ty.is_int());
+            assert!(lane_count <= u64::from(res_ty.bits()));
+
+            let mut res = fx.bcx.ins().iconst(res_ty, 0);
 
             for lane in (0..lane_count).rev() {
                 let a_lane = a.value_lane(fx, lane).load_scalar(fx);
@@ -42,61 +55,419 @@ pub(crate) fn codegen_x86_llvm_intrinsic_call<'tcx>(
                 let a_lane_sign = fx.bcx.ins().ushr_imm(a_lane, i64::from(lane_ty.bits() - 1));
 
                 // shift sign bit into result
-                let a_lane_sign = clif_intcast(fx, a_lane_sign, types::I32, false);
+                let a_lane_sign = clif_intcast(fx, a_lane_sign, res_ty, false);
                 res = fx.bcx.ins().ishl_imm(res, 1);
                 res = fx.bcx.ins().bor(res, a_lane_sign);
             }
 
-            let res = CValue::by_val(res, fx.layout_of(fx.tcx.types.i32));
+            let res = CValue::by_val(res, ret.layout());
             ret.write_cvalue(fx, res);
         }
-        "llvm.x86.sse2.cmp.ps" | "llvm.x86.sse2.cmp.pd" => {
+        "llvm.x86.sse.cmp.ps"
+        | "llvm.x86.sse2.cmp.pd"
+        | "llvm.x86.avx.cmp.ps.256"
+        | "llvm.x86.avx.cmp.pd.256" => {
+            // https://www.felixcloutier.com/x86/cmpps
+            // https://www.felixcloutier.com/x86/cmppd
             let (x, y, kind) = match args {
                 [x, y, kind] => (x, y, kind),
                 _ => bug!("wrong number of args for intrinsic {intrinsic}"),
             };
             let x = codegen_operand(fx, x);
             let y = codegen_operand(fx, y);
-            let kind = crate::constant::mir_operand_get_const_val(fx, kind)
-                .expect("llvm.x86.sse2.cmp.* kind not const");
-
-            let flt_cc = match kind
-                .try_to_bits(Size::from_bytes(1))
-                .unwrap_or_else(|| panic!("kind not scalar: {:?}", kind))
-            {
-                0 => FloatCC::Equal,
-                1 => FloatCC::LessThan,
-                2 => FloatCC::LessThanOrEqual,
-                7 => FloatCC::Ordered,
-                3 => FloatCC::Unordered,
-                4 => FloatCC::NotEqual,
-                5 => FloatCC::UnorderedOrGreaterThanOrEqual,
-                6 => FloatCC::UnorderedOrGreaterThan,
-                kind => unreachable!("kind {:?}", kind),
+            let kind = intrinsic_const_u8(fx, kind, intrinsic, span);
+            let cmp = match cmp_kind_to_float_cmp(kind) {
+                Ok(cmp) => cmp,
+                Err(err) => {
+                    report_intrinsic_error(fx, intrinsic, span, err);
+                    return;
+                }
             };
 
             simd_pair_for_each_lane(fx, x, y, ret, &|fx, lane_ty, res_lane_ty, x_lane, y_lane| {
                 let res_lane = match lane_ty.kind() {
-                    ty::Float(_) => fx.bcx.ins().fcmp(flt_cc, x_lane, y_lane),
+                    ty::Float(_) => x86_float_cmp(fx, cmp, x_lane, y_lane),
                     _ => unreachable!("{:?}", lane_ty),
                 };
-                bool_to_zero_or_max_uint(fx, res_lane_ty, res_lane)
+                let res_lane_layout = fx.layout_of(res_lane_ty);
+                bool_to_lane_mask(fx, res_lane_layout, res_lane)
+            });
+        }
+        "llvm.x86.sse.cmp.ss" | "llvm.x86.sse2.cmp.sd" => {
+            let (x, y, kind) = match args {
+                [x, y, kind] => (x, y, kind),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let x = codegen_operand(fx, x);
+            let y = codegen_operand(fx, y);
+            let kind = intrinsic_const_u8(fx, kind, intrinsic, span);
+            let cmp = match cmp_kind_to_float_cmp(kind) {
+                Ok(cmp) => cmp,
+                Err(err) => {
+                    report_intrinsic_error(fx, intrinsic, span, err);
+                    return;
+                }
+            };
+
+            // Only lane 0 is compared, the upper lanes are passed through from the first operand
+            let x_lane = x.value_lane(fx, 0).load_scalar(fx);
+            let y_lane = y.value_lane(fx, 0).load_scalar(fx);
+            let res_lane = x86_float_cmp(fx, cmp, x_lane, y_lane);
+            let res_lane_layout = ret.layout().field(fx, 0);
+            let res_lane = bool_to_lane_mask(fx, res_lane_layout, res_lane);
+
+            ret.write_cvalue(fx, x);
+            ret.place_lane(fx, 0).write_cvalue(fx, CValue::by_val(res_lane, res_lane_layout));
+        }
+        "llvm.x86.sse.comieq.ss"
+        | "llvm.x86.sse.comilt.ss"
+        | "llvm.x86.sse.comile.ss"
+        | "llvm.x86.sse.comigt.ss"
+        | "llvm.x86.sse.comige.ss"
+        | "llvm.x86.sse.comineq.ss"
+        | "llvm.x86.sse.ucomieq.ss"
+        | "llvm.x86.sse.ucomilt.ss"
+        | "llvm.x86.sse.ucomile.ss"
+        | "llvm.x86.sse.ucomigt.ss"
+        | "llvm.x86.sse.ucomige.ss"
+        | "llvm.x86.sse.ucomineq.ss"
+        | "llvm.x86.sse2.comieq.sd"
+        | "llvm.x86.sse2.comilt.sd"
+        | "llvm.x86.sse2.comile.sd"
+        | "llvm.x86.sse2.comigt.sd"
+        | "llvm.x86.sse2.comige.sd"
+        | "llvm.x86.sse2.comineq.sd"
+        | "llvm.x86.sse2.ucomieq.sd"
+        | "llvm.x86.sse2.ucomilt.sd"
+        | "llvm.x86.sse2.ucomile.sd"
+        | "llvm.x86.sse2.ucomigt.sd"
+        | "llvm.x86.sse2.ucomige.sd"
+        | "llvm.x86.sse2.ucomineq.sd" => {
+            // https://www.felixcloutier.com/x86/comiss
+            // https://www.felixcloutier.com/x86/ucomiss
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            // The comi and ucomi variants only differ in which NaNs raise an invalid operation
+            // exception. All comparisons are false for unordered inputs except for neq.
+            let kind = intrinsic.split('.').nth(3).unwrap();
+            let flt_cc = match kind.trim_start_matches("ucomi").trim_start_matches("comi") {
+                "eq" => FloatCC::Equal,
+                "lt" => FloatCC::LessThan,
+                "le" => FloatCC::LessThanOrEqual,
+                "gt" => FloatCC::GreaterThan,
+                "ge" => FloatCC::GreaterThanOrEqual,
+                "neq" => FloatCC::NotEqual,
+                kind => unreachable!("{kind}"),
+            };
+
+            let a_lane = a.value_lane(fx, 0).load_scalar(fx);
+            let b_lane = b.value_lane(fx, 0).load_scalar(fx);
+            let res = fx.bcx.ins().fcmp(flt_cc, a_lane, b_lane);
+            let res = fx.bcx.ins().uextend(types::I32, res);
+            ret.write_cvalue(fx, CValue::by_val(res, fx.layout_of(fx.tcx.types.i32)));
+        }
+        "llvm.x86.sse.max.ps"
+        | "llvm.x86.sse.min.ps"
+        | "llvm.x86.sse2.max.pd"
+        | "llvm.x86.sse2.min.pd"
+        | "llvm.x86.avx.max.ps.256"
+        | "llvm.x86.avx.min.ps.256"
+        | "llvm.x86.avx.max.pd.256"
+        | "llvm.x86.avx.min.pd.256" => {
+            // https://www.felixcloutier.com/x86/maxps
+            // https://www.felixcloutier.com/x86/minps
+            intrinsic_args!(fx, args => (x, y); intrinsic);
+
+            let is_max = intrinsic.contains(".max.");
+            simd_pair_for_each_lane(
+                fx,
+                x,
+                y,
+                ret,
+                &|fx, _lane_ty, _res_lane_ty, x_lane, y_lane| {
+                    x86_float_min_max(fx, is_max, x_lane, y_lane)
+                },
+            );
+        }
+        "llvm.x86.sse.max.ss"
+        | "llvm.x86.sse.min.ss"
+        | "llvm.x86.sse2.max.sd"
+        | "llvm.x86.sse2.min.sd" => {
+            // https://www.felixcloutier.com/x86/maxss
+            // https://www.felixcloutier.com/x86/minss
+            intrinsic_args!(fx, args => (x, y); intrinsic);
+
+            // Only lane 0 is computed, the upper lanes are passed through from the first operand
+            let is_max = intrinsic.contains(".max.");
+            let x_lane = x.value_lane(fx, 0).load_scalar(fx);
+            let y_lane = y.value_lane(fx, 0).load_scalar(fx);
+            let res_lane = x86_float_min_max(fx, is_max, x_lane, y_lane);
+            let res_lane_layout = ret.layout().field(fx, 0);
+
+            ret.write_cvalue(fx, x);
+            ret.place_lane(fx, 0).write_cvalue(fx, CValue::by_val(res_lane, res_lane_layout));
+        }
+        "llvm.x86.sse3.hadd.ps"
+        | "llvm.x86.sse3.hadd.pd"
+        | "llvm.x86.sse3.hsub.ps"
+        | "llvm.x86.sse3.hsub.pd"
+        | "llvm.x86.avx.hadd.ps.256"
+        | "llvm.x86.avx.hadd.pd.256"
+        | "llvm.x86.avx.hsub.ps.256"
+        | "llvm.x86.avx.hsub.pd.256" => {
+            // https://www.felixcloutier.com/x86/haddps
+            // https://www.felixcloutier.com/x86/haddpd
+            // https://www.felixcloutier.com/x86/hsubps
+            // https://www.felixcloutier.com/x86/hsubpd
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            let is_add = intrinsic.contains(".hadd.");
+            simd_horizontal_pair(fx, a, b, ret, &|fx, x_lane, y_lane| {
+                if is_add {
+                    fx.bcx.ins().fadd(x_lane, y_lane)
+                } else {
+                    fx.bcx.ins().fsub(x_lane, y_lane)
+                }
+            });
+        }
+        "llvm.x86.sse.rcp.ps"
+        | "llvm.x86.sse.rsqrt.ps"
+        | "llvm.x86.avx.rcp.ps.256"
+        | "llvm.x86.avx.rsqrt.ps.256" => {
+            // https://www.felixcloutier.com/x86/rcpps
+            // https://www.felixcloutier.com/x86/rsqrtps
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            // The hardware only guarantees a relative error of at most 1.5 * 2^-12. Computing the
+            // exact value satisfies that, but is more precise than what the hardware returns.
+            let is_rsqrt = intrinsic.contains(".rsqrt.");
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| {
+                x86_rcp_rsqrt(fx, is_rsqrt, lane)
+            });
+        }
+        "llvm.x86.sse.rcp.ss" | "llvm.x86.sse.rsqrt.ss" => {
+            // https://www.felixcloutier.com/x86/rcpss
+            // https://www.felixcloutier.com/x86/rsqrtss
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            // Only lane 0 is computed, the upper lanes are passed through
+            let is_rsqrt = intrinsic.contains(".rsqrt.");
+            let lane = a.value_lane(fx, 0).load_scalar(fx);
+            let res_lane = x86_rcp_rsqrt(fx, is_rsqrt, lane);
+            let res_lane_layout = ret.layout().field(fx, 0);
+
+            ret.write_cvalue(fx, a);
+            ret.place_lane(fx, 0).write_cvalue(fx, CValue::by_val(res_lane, res_lane_layout));
+        }
+        "llvm.x86.sse41.round.ps"
+        | "llvm.x86.sse41.round.pd"
+        | "llvm.x86.avx.round.ps.256"
+        | "llvm.x86.avx.round.pd.256" => {
+            // https://www.felixcloutier.com/x86/roundps
+            // https://www.felixcloutier.com/x86/roundpd
+            let (a, rounding) = match args {
+                [a, rounding] => (a, rounding),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let rounding = intrinsic_const_u8(fx, rounding, intrinsic, span);
+
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| {
+                x86_round(fx, lane, rounding)
+            });
+        }
+        "llvm.x86.sse41.round.ss" | "llvm.x86.sse41.round.sd" => {
+            // https://www.felixcloutier.com/x86/roundss
+            // https://www.felixcloutier.com/x86/roundsd
+            let (a, b, rounding) = match args {
+                [a, b, rounding] => (a, b, rounding),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let rounding = intrinsic_const_u8(fx, rounding, intrinsic, span);
+
+            // Only lane 0 of b is rounded, the upper lanes are passed through from a
+            let b_lane = b.value_lane(fx, 0).load_scalar(fx);
+            let res_lane = x86_round(fx, b_lane, rounding);
+            let res_lane_layout = ret.layout().field(fx, 0);
+
+            ret.write_cvalue(fx, a);
+            ret.place_lane(fx, 0).write_cvalue(fx, CValue::by_val(res_lane, res_lane_layout));
+        }
+        "llvm.x86.sse2.cvtdq2ps" | "llvm.x86.avx.cvtdq2.ps.256" => {
+            // _mm_cvtepi32_pd is lowered to simd_cast by stdarch, only the f32 variant is an LLVM
+            // intrinsic
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, res_lane_ty, lane| {
+                let res_lane_ty = fx.clif_type(res_lane_ty).unwrap();
+                fx.bcx.ins().fcvt_from_sint(res_lane_ty, lane)
             });
         }
+        "llvm.x86.sse.cvtpi2pd" => {
+            // https://www.felixcloutier.com/x86/cvtpi2pd
+            // `_mm_cvtpi32_pd` has been removed from core::arch together with the rest of the MMX
+            // intrinsics, so this is only reachable when declaring the intrinsic directly. The
+            // MMX operand is treated as a pair of i32.
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            assert_eq!(lane_count, 2);
+            assert_eq!(lane_ty, fx.tcx.types.i32);
+
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, res_lane_ty, lane| {
+                let res_lane_ty = fx.clif_type(res_lane_ty).unwrap();
+                fx.bcx.ins().fcvt_from_sint(res_lane_ty, lane)
+            });
+        }
+        "llvm.x86.sse2.cvtps2pd" => {
+            // https://www.felixcloutier.com/x86/cvtps2pd
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let (ret_lane_count, ret_lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            assert_eq!(lane_ty, fx.tcx.types.f32);
+            assert_eq!(ret_lane_ty, fx.tcx.types.f64);
+            assert_eq!(lane_count, ret_lane_count * 2);
+            let ret_lane_layout = ret.layout().field(fx, 0);
+
+            // Only the low lanes are converted. Every f32 including subnormals is exactly
+            // representable as f64, so this doesn't depend on the rounding mode.
+            for lane_idx in 0..ret_lane_count {
+                let lane = a.value_lane(fx, lane_idx).load_scalar(fx);
+                let res_lane = fx.bcx.ins().fpromote(types::F64, lane);
+                ret.place_lane(fx, lane_idx)
+                    .write_cvalue(fx, CValue::by_val(res_lane, ret_lane_layout));
+            }
+        }
+        "llvm.x86.sse.cvtss2si"
+        | "llvm.x86.sse.cvttss2si"
+        | "llvm.x86.sse.cvtss2si64"
+        | "llvm.x86.sse.cvttss2si64"
+        | "llvm.x86.sse2.cvtsd2si"
+        | "llvm.x86.sse2.cvttsd2si"
+        | "llvm.x86.sse2.cvtsd2si64"
+        | "llvm.x86.sse2.cvttsd2si64" => {
+            // https://www.felixcloutier.com/x86/cvtss2si
+            // https://www.felixcloutier.com/x86/cvttss2si
+            // https://www.felixcloutier.com/x86/cvtsd2si
+            // https://www.felixcloutier.com/x86/cvttsd2si
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let truncate = intrinsic.contains(".cvtt");
+            let int_ty = fx.clif_type(ret.layout().ty).unwrap();
+            assert!(int_ty == types::I32 || int_ty == types::I64);
+
+            // Only lane 0 is converted
+            let lane = a.value_lane(fx, 0).load_scalar(fx);
+            let res = float_to_int_or_indefinite(fx, lane, int_ty, truncate);
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+        }
+        "llvm.x86.sse.cvtsi2ss" | "llvm.x86.sse.cvtsi642ss" => {
+            // https://www.felixcloutier.com/x86/cvtsi2ss
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            // Only lane 0 is replaced, the upper lanes are passed through from the first operand
+            let b = b.load_scalar(fx);
+            let res_lane = fx.bcx.ins().fcvt_from_sint(types::F32, b);
+            let res_lane_layout = ret.layout().field(fx, 0);
+
+            ret.write_cvalue(fx, a);
+            ret.place_lane(fx, 0).write_cvalue(fx, CValue::by_val(res_lane, res_lane_layout));
+        }
+        "llvm.x86.sse2.cvtps2dq"
+        | "llvm.x86.sse2.cvttps2dq"
+        | "llvm.x86.sse2.cvtpd2dq"
+        | "llvm.x86.sse2.cvttpd2dq"
+        | "llvm.x86.avx.cvt.ps2dq.256"
+        | "llvm.x86.avx.cvtt.ps2dq.256"
+        | "llvm.x86.avx.cvt.pd2dq.256"
+        | "llvm.x86.avx.cvtt.pd2dq.256" => {
+            // https://www.felixcloutier.com/x86/cvtps2dq
+            // https://www.felixcloutier.com/x86/cvttps2dq
+            // https://www.felixcloutier.com/x86/cvtpd2dq
+            // https://www.felixcloutier.com/x86/cvttpd2dq
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let truncate = intrinsic.contains(".cvtt");
+            let (lane_count, _lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let (ret_lane_count, _ret_lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            let ret_lane_layout = ret.layout().field(fx, 0);
+            assert!(lane_count <= ret_lane_count);
+
+            for lane_idx in 0..lane_count {
+                let lane = a.value_lane(fx, lane_idx).load_scalar(fx);
+                let res_lane = float_to_int_or_indefinite(fx, lane, types::I32, truncate);
+                ret.place_lane(fx, lane_idx)
+                    .write_cvalue(fx, CValue::by_val(res_lane, ret_lane_layout));
+            }
+
+            // cvtpd2dq and cvttpd2dq zero the upper half of the result
+            for lane_idx in lane_count..ret_lane_count {
+                let zero = fx.bcx.ins().iconst(types::I32, 0);
+                ret.place_lane(fx, lane_idx)
+                    .write_cvalue(fx, CValue::by_val(zero, ret_lane_layout));
+            }
+        }
+        "llvm.x86.avx512.mask.cvtps2dq.512"
+        | "llvm.x86.avx512.mask.cvtpd2dq.512"
+        | "llvm.x86.avx512.mask.cvttps2dq.512"
+        | "llvm.x86.avx512.mask.cvttps2dq.256"
+        | "llvm.x86.avx512.mask.cvttps2dq.128" => {
+            // https://www.felixcloutier.com/x86/cvtps2dq
+            // https://www.felixcloutier.com/x86/cvtpd2dq
+            // https://www.felixcloutier.com/x86/cvttps2dq
+            let (a, src, mask, rounding) = match args {
+                [a, src, mask, rounding] => (a, src, mask, Some(rounding)),
+                [a, src, mask] => (a, src, mask, None),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let src = codegen_operand(fx, src);
+            let mask = codegen_operand(fx, mask).load_scalar(fx);
+            // The rounding operand of the truncating conversions only suppresses exceptions
+            let rounding = match rounding {
+                Some(rounding) if !intrinsic.contains(".cvtt") => {
+                    Some(intrinsic_const_u8(fx, rounding, intrinsic, span))
+                }
+                _ => None,
+            };
+
+            let (lane_count, _lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let (ret_lane_count, _ret_lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            let ret_lane_layout = ret.layout().field(fx, 0);
+            assert_eq!(lane_count, ret_lane_count);
+
+            // Lanes whose mask bit is clear are taken from src, which is zero for the maskz variants
+            for lane_idx in 0..lane_count {
+                let lane = a.value_lane(fx, lane_idx).load_scalar(fx);
+                let res_lane = match rounding {
+                    Some(rounding) => {
+                        // The rounded value is integral, so truncating it doesn't change it
+                        let rounded = x86_round(fx, lane, rounding);
+                        float_to_int_or_indefinite(fx, rounded, types::I32, true)
+                    }
+                    None => float_to_int_or_indefinite(fx, lane, types::I32, true),
+                };
+                let src_lane = src.value_lane(fx, lane_idx).load_scalar(fx);
+                let bit = fx.bcx.ins().ushr_imm(mask, lane_idx as i64);
+                let bit = fx.bcx.ins().band_imm(bit, 1);
+                let res_lane = fx.bcx.ins().select(bit, res_lane, src_lane);
+                ret.place_lane(fx, lane_idx)
+                    .write_cvalue(fx, CValue::by_val(res_lane, ret_lane_layout));
+            }
+        }
         "llvm.x86.sse2.psrli.d" => {
             let (a, imm8) = match args {
                 [a, imm8] => (a, imm8),
                 _ => bug!("wrong number of args for intrinsic {intrinsic}"),
             };
             let a = codegen_operand(fx, a);
-            let imm8 = crate::constant::mir_operand_get_const_val(fx, imm8)
-                .expect("llvm.x86.sse2.psrli.d imm8 not const");
-
-            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| match imm8
-                .try_to_bits(Size::from_bytes(4))
-                .unwrap_or_else(|| panic!("imm8 not scalar: {:?}", imm8))
-            {
-                imm8 if imm8 < 32 => fx.bcx.ins().ushr_imm(lane, i64::from(imm8 as u8)),
+            let imm8 = intrinsic_const_u8(fx, imm8, intrinsic, span);
+
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| match imm8 {
+                imm8 if imm8 < 32 => fx.bcx.ins().ushr_imm(lane, i64::from(imm8)),
                 _ => fx.bcx.ins().iconst(types::I32, 0),
             });
         }
@@ -106,17 +477,91 @@ pub(crate) fn codegen_x86_llvm_intrinsic_call<'tcx>(
                 _ => bug!("wrong number of args for intrinsic {intrinsic}"),
             };
             let a = codegen_operand(fx, a);
-            let imm8 = crate::constant::mir_operand_get_const_val(fx, imm8)
-                .expect("llvm.x86.sse2.psrli.d imm8 not const");
-
-            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| match imm8
-                .try_to_bits(Size::from_bytes(4))
-                .unwrap_or_else(|| panic!("imm8 not scalar: {:?}", imm8))
-            {
-                imm8 if imm8 < 32 => fx.bcx.ins().ishl_imm(lane, i64::from(imm8 as u8)),
+            let imm8 = intrinsic_const_u8(fx, imm8, intrinsic, span);
+
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| match imm8 {
+                imm8 if imm8 < 32 => fx.bcx.ins().ishl_imm(lane, i64::from(imm8)),
                 _ => fx.bcx.ins().iconst(types::I32, 0),
             });
         }
+        "llvm.x86.avx2.psllv.d"
+        | "llvm.x86.avx2.psllv.d.256"
+        | "llvm.x86.avx2.psllv.q"
+        | "llvm.x86.avx2.psllv.q.256"
+        | "llvm.x86.avx2.psrlv.d"
+        | "llvm.x86.avx2.psrlv.d.256"
+        | "llvm.x86.avx2.psrlv.q"
+        | "llvm.x86.avx2.psrlv.q.256"
+        | "llvm.x86.avx2.psrav.d"
+        | "llvm.x86.avx2.psrav.d.256" => {
+            // https://www.felixcloutier.com/x86/vpsllvw:vpsllvd:vpsllvq
+            // https://www.felixcloutier.com/x86/vpsrlvw:vpsrlvd:vpsrlvq
+            // https://www.felixcloutier.com/x86/vpsravw:vpsravd:vpsravq
+            intrinsic_args!(fx, args => (a, count); intrinsic);
+
+            simd_pair_for_each_lane(
+                fx,
+                a,
+                count,
+                ret,
+                &|fx, lane_ty, _res_lane_ty, a_lane, count_lane| {
+                    let lane_ty = fx.clif_type(lane_ty).unwrap();
+                    let lane_bits = i64::from(lane_ty.bits());
+
+                    // Every lane has its own count, which is treated as unsigned. Unlike the
+                    // Cranelift shifts, counts of at least the lane width aren't masked: they
+                    // shift in zeros or, for the arithmetic shift, copies of the sign bit.
+                    let in_range =
+                        fx.bcx.ins().icmp_imm(IntCC::UnsignedLessThan, count_lane, lane_bits);
+                    if intrinsic.contains(".psrav.") {
+                        let max_count = fx.bcx.ins().iconst(lane_ty, lane_bits - 1);
+                        let count_lane = fx.bcx.ins().select(in_range, count_lane, max_count);
+                        fx.bcx.ins().sshr(a_lane, count_lane)
+                    } else {
+                        let res_lane = if intrinsic.contains(".psllv.") {
+                            fx.bcx.ins().ishl(a_lane, count_lane)
+                        } else {
+                            fx.bcx.ins().ushr(a_lane, count_lane)
+                        };
+                        let zero = fx.bcx.ins().iconst(lane_ty, 0);
+                        fx.bcx.ins().select(in_range, res_lane, zero)
+                    }
+                },
+            );
+        }
+        "llvm.x86.sse2.psrai.w" | "llvm.x86.sse2.psrai.d" => {
+            // https://www.felixcloutier.com/x86/psraw:psrad:psraq
+            let (a, imm8) = match args {
+                [a, imm8] => (a, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let imm8 = intrinsic_const_u8(fx, imm8, intrinsic, span);
+
+            // Shifting by more than the lane width fills the lane with the sign bit, which is the
+            // same as shifting by the lane width minus one.
+            simd_for_each_lane(fx, a, ret, &|fx, lane_ty, _res_lane_ty, lane| {
+                let max_shift = fx.layout_of(lane_ty).size.bits() - 1;
+                fx.bcx.ins().sshr_imm(lane, i64::from(imm8).min(max_shift as i64))
+            });
+        }
+        "llvm.x86.sse2.psra.w" | "llvm.x86.sse2.psra.d" => {
+            // https://www.felixcloutier.com/x86/psraw:psrad:psraq
+            intrinsic_args!(fx, args => (a, count); intrinsic);
+
+            // The shift count is taken from the low 64 bits of the count vector
+            let count = count.load_scalar(fx);
+            let count = codegen_bitcast(fx, types::I64X2, count);
+            let count = fx.bcx.ins().extractlane(count, 0);
+
+            simd_for_each_lane(fx, a, ret, &|fx, lane_ty, _res_lane_ty, lane| {
+                let max_shift = fx.layout_of(lane_ty).size.bits() - 1;
+                let max_shift = fx.bcx.ins().iconst(types::I64, max_shift as i64);
+                let is_over_width = fx.bcx.ins().icmp(IntCC::UnsignedGreaterThan, count, max_shift);
+                let count = fx.bcx.ins().select(is_over_width, max_shift, count);
+                fx.bcx.ins().sshr(lane, count)
+            });
+        }
         "llvm.x86.sse2.storeu.dq" => {
             intrinsic_args!(fx, args => (mem_addr, a); intrinsic);
             let mem_addr = mem_addr.load_scalar(fx);
@@ -125,23 +570,923 @@ pub(crate) fn codegen_x86_llvm_intrinsic_call<'tcx>(
             let dest = CPlace::for_ptr(Pointer::new(mem_addr), a.layout());
             dest.write_cvalue(fx, a);
         }
+        "llvm.x86.sse2.maskmov.dqu" => {
+            // https://www.felixcloutier.com/x86/maskmovdqu
+            intrinsic_args!(fx, args => (a, mask, mem_addr); intrinsic);
+            // The stores must use the pointer exactly as passed in as base address. Rebuilding it
+            // from an integer of a different width or from a separately computed address would
+            // lose the link to the allocation it was derived from.
+            let mem_addr = mem_addr.load_scalar(fx);
+
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            assert_eq!(lane_ty, fx.tcx.types.i8);
+
+            // Every enabled byte is written with a separate single byte store and disabled bytes
+            // aren't accessed at all. There is no read-modify-write of the whole 16 byte region,
+            // so no memory outside of mem_addr..mem_addr + 16 is ever touched. This matters as
+            // the destination may end right at the end of an allocation or before a guard page.
+            for lane_idx in 0..lane_count {
+                let a_lane = a.value_lane(fx, lane_idx).load_scalar(fx);
+                let mask_lane = mask.value_lane(fx, lane_idx).load_scalar(fx);
+
+                // Only the sign bit of every mask byte decides if the byte is written
+                let enabled = fx.bcx.ins().icmp_imm(IntCC::SignedLessThan, mask_lane, 0);
+
+                let if_enabled = fx.bcx.create_block();
+                let next = fx.bcx.create_block();
+
+                fx.bcx.ins().brif(enabled, if_enabled, &[], next, &[]);
+                fx.bcx.seal_block(if_enabled);
+
+                fx.bcx.switch_to_block(if_enabled);
+                fx.bcx.ins().store(MemFlags::trusted(), a_lane, mem_addr, lane_idx as i32);
+                fx.bcx.ins().jump(next, &[]);
+
+                fx.bcx.seal_block(next);
+                fx.bcx.switch_to_block(next);
+            }
+        }
+        "llvm.x86.avx2.permd" | "llvm.x86.avx2.permps" => {
+            // https://www.felixcloutier.com/x86/vpermd:vpermw
+            // https://www.felixcloutier.com/x86/vpermps
+            intrinsic_args!(fx, args => (a, idx); intrinsic);
+
+            let (lane_count, _lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let (idx_lane_count, _idx_lane_ty) = idx.layout().ty.simd_size_and_type(fx.tcx);
+            assert_eq!(lane_count, 8);
+            assert_eq!(idx_lane_count, 8);
+
+            // Unlike most shuffles this can move lanes across the 128bit halves
+            for out_lane_idx in 0..lane_count {
+                let idx_lane = idx.value_lane(fx, out_lane_idx).load_scalar(fx);
+                let idx_lane = fx.bcx.ins().band_imm(idx_lane, 0b111);
+                let res_lane = select_lane_dynamic(fx, a, 0, lane_count, idx_lane);
+                let res_lane = CValue::by_val(res_lane, ret.layout().field(fx, 0));
+                ret.place_lane(fx, out_lane_idx).write_cvalue(fx, res_lane);
+            }
+        }
+        "llvm.x86.avx.vpermilvar.ps"
+        | "llvm.x86.avx.vpermilvar.ps.256"
+        | "llvm.x86.avx.vpermilvar.pd"
+        | "llvm.x86.avx.vpermilvar.pd.256" => {
+            // https://www.felixcloutier.com/x86/vpermilps
+            // https://www.felixcloutier.com/x86/vpermilpd
+            intrinsic_args!(fx, args => (a, control); intrinsic);
+
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let (control_lane_count, _control_lane_ty) =
+                control.layout().ty.simd_size_and_type(fx.tcx);
+            assert_eq!(lane_count, control_lane_count);
+
+            // Lanes are only permuted within their own 128bit half
+            let lanes_per_half = 16 / fx.layout_of(lane_ty).size.bytes();
+            for out_lane_idx in 0..lane_count {
+                let control_lane = control.value_lane(fx, out_lane_idx).load_scalar(fx);
+                let sel = if lanes_per_half == 4 {
+                    fx.bcx.ins().band_imm(control_lane, 0b11)
+                } else {
+                    // vpermilpd selects using bit 1 instead of bit 0
+                    let sel = fx.bcx.ins().ushr_imm(control_lane, 1);
+                    fx.bcx.ins().band_imm(sel, 0b1)
+                };
+                let lane_base = out_lane_idx / lanes_per_half * lanes_per_half;
+                let res_lane = select_lane_dynamic(fx, a, lane_base, lanes_per_half, sel);
+                let res_lane = CValue::by_val(res_lane, ret.layout().field(fx, 0));
+                ret.place_lane(fx, out_lane_idx).write_cvalue(fx, res_lane);
+            }
+        }
+        "llvm.x86.ssse3.pabs.b.128"
+        | "llvm.x86.ssse3.pabs.w.128"
+        | "llvm.x86.ssse3.pabs.d.128"
+        | "llvm.x86.avx2.pabs.b"
+        | "llvm.x86.avx2.pabs.w"
+        | "llvm.x86.avx2.pabs.d" => {
+            // https://www.felixcloutier.com/x86/pabsb:pabsw:pabsd:pabsq
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            simd_for_each_lane(fx, a, ret, &|fx, _lane_ty, _res_lane_ty, lane| {
+                // The minimum value wraps to itself like on real hardware
+                let neg_lane = fx.bcx.ins().ineg(lane);
+                let is_neg = fx.bcx.ins().icmp_imm(IntCC::SignedLessThan, lane, 0);
+                fx.bcx.ins().select(is_neg, neg_lane, lane)
+            });
+        }
+        "llvm.x86.ssse3.psign.b.128"
+        | "llvm.x86.ssse3.psign.w.128"
+        | "llvm.x86.ssse3.psign.d.128"
+        | "llvm.x86.avx2.psign.b"
+        | "llvm.x86.avx2.psign.w"
+        | "llvm.x86.avx2.psign.d" => {
+            // https://www.felixcloutier.com/x86/psignb:psignw:psignd
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            simd_pair_for_each_lane(fx, a, b, ret, &|fx, lane_ty, _res_lane_ty, a_lane, b_lane| {
+                let lane_ty = fx.clif_type(lane_ty).unwrap();
+                let zero = fx.bcx.ins().iconst(lane_ty, 0);
+                let neg_a_lane = fx.bcx.ins().ineg(a_lane);
+
+                let b_is_neg = fx.bcx.ins().icmp_imm(IntCC::SignedLessThan, b_lane, 0);
+                let b_is_zero = fx.bcx.ins().icmp_imm(IntCC::Equal, b_lane, 0);
+                let res_lane = fx.bcx.ins().select(b_is_neg, neg_a_lane, a_lane);
+                fx.bcx.ins().select(b_is_zero, zero, res_lane)
+            });
+        }
+        "llvm.x86.sse41.phminposuw" => {
+            // https://www.felixcloutier.com/x86/phminposuw
+            intrinsic_args!(fx, args => (a); intrinsic);
+
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_layout = fx.layout_of(lane_ty);
+            assert_eq!(lane_ty, fx.tcx.types.u16);
+            assert_eq!(lane_count, 8);
+
+            let mut min = a.value_lane(fx, 0).load_scalar(fx);
+            let mut min_idx = fx.bcx.ins().iconst(types::I16, 0);
+            for lane_idx in 1..lane_count {
+                let lane = a.value_lane(fx, lane_idx).load_scalar(fx);
+                // Strictly less than to keep the lowest index on ties
+                let is_less = fx.bcx.ins().icmp(IntCC::UnsignedLessThan, lane, min);
+                let lane_idx = fx.bcx.ins().iconst(types::I16, lane_idx as i64);
+                min = fx.bcx.ins().select(is_less, lane, min);
+                min_idx = fx.bcx.ins().select(is_less, lane_idx, min_idx);
+            }
+
+            let zero = fx.bcx.ins().iconst(types::I16, 0);
+            for out_lane_idx in 0..lane_count {
+                let res_lane = match out_lane_idx {
+                    0 => min,
+                    1 => min_idx,
+                    _ => zero,
+                };
+                let res_lane = CValue::by_val(res_lane, lane_layout);
+                ret.place_lane(fx, out_lane_idx).write_cvalue(fx, res_lane);
+            }
+        }
+        "llvm.x86.sse41.blendps" | "llvm.x86.sse41.blendpd" | "llvm.x86.sse41.pblendw" => {
+            // https://www.felixcloutier.com/x86/blendps
+            // https://www.felixcloutier.com/x86/blendpd
+            // https://www.felixcloutier.com/x86/pblendw
+            let (a, b, imm8) = match args {
+                [a, b, imm8] => (a, b, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let imm8 = intrinsic_const_u8(fx, imm8, intrinsic, span);
+
+            let (lane_count, _lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            assert!(lane_count <= 8);
+
+            for lane_idx in 0..lane_count {
+                let src = if imm8 & (1 << lane_idx) != 0 { b } else { a };
+                let res_lane = src.value_lane(fx, lane_idx);
+                ret.place_lane(fx, lane_idx).write_cvalue(fx, res_lane);
+            }
+        }
+        "llvm.x86.sse41.pblendvb"
+        | "llvm.x86.sse41.blendvps"
+        | "llvm.x86.sse41.blendvpd"
+        | "llvm.x86.avx2.pblendvb"
+        | "llvm.x86.avx.blendv.ps.256"
+        | "llvm.x86.avx.blendv.pd.256" => {
+            // https://www.felixcloutier.com/x86/pblendvb
+            // https://www.felixcloutier.com/x86/blendvps
+            // https://www.felixcloutier.com/x86/blendvpd
+            intrinsic_args!(fx, args => (a, b, mask); intrinsic);
+
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_ty = fx.clif_type(lane_ty).unwrap();
+            let lane_layout = ret.layout().field(fx, 0);
+
+            for lane_idx in 0..lane_count {
+                let a_lane = a.value_lane(fx, lane_idx).load_scalar(fx);
+                let b_lane = b.value_lane(fx, lane_idx).load_scalar(fx);
+                let mask_lane = mask.value_lane(fx, lane_idx).load_scalar(fx);
+
+                // Only the sign bit of the mask lane is used, so -0.0 and negative NaN select b
+                let mask_lane = match lane_ty {
+                    types::F32 => codegen_bitcast(fx, types::I32, mask_lane),
+                    types::F64 => codegen_bitcast(fx, types::I64, mask_lane),
+                    _ => mask_lane,
+                };
+                let use_b = fx.bcx.ins().icmp_imm(IntCC::SignedLessThan, mask_lane, 0);
+                let res_lane = fx.bcx.ins().select(use_b, b_lane, a_lane);
+                ret.place_lane(fx, lane_idx)
+                    .write_cvalue(fx, CValue::by_val(res_lane, lane_layout));
+            }
+        }
+        "llvm.x86.sse41.dpps" | "llvm.x86.sse41.dppd" | "llvm.x86.avx.dp.ps.256" => {
+            // https://www.felixcloutier.com/x86/dpps
+            // https://www.felixcloutier.com/x86/dppd
+            let (a, b, imm8) = match args {
+                [a, b, imm8] => (a, b, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let imm8 = intrinsic_const_u8(fx, imm8, intrinsic, span);
+
+            assert_eq!(a.layout(), b.layout());
+            assert_eq!(a.layout(), ret.layout());
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_layout = fx.layout_of(lane_ty);
+            let zero = match fx.clif_type(lane_ty).unwrap() {
+                types::F32 => fx.bcx.ins().f32const(0.0),
+                types::F64 => fx.bcx.ins().f64const(0.0),
+                ty => unreachable!("{:?}", ty),
+            };
+
+            // The 256bit variant computes a separate dot product for every 128bit half using the
+            // same immediate
+            let lanes_per_half = 16 / lane_layout.size.bytes();
+            for half_base in (0..lane_count).step_by(lanes_per_half as usize) {
+                // The high four bits of the immediate select which products are summed, lanes
+                // which aren't selected contribute 0.0
+                let mut terms = (0..lanes_per_half)
+                    .map(|lane_idx| {
+                        if imm8 & (1 << (4 + lane_idx)) == 0 {
+                            return zero;
+                        }
+                        let a_lane = a.value_lane(fx, half_base + lane_idx).load_scalar(fx);
+                        let b_lane = b.value_lane(fx, half_base + lane_idx).load_scalar(fx);
+                        fx.bcx.ins().fmul(a_lane, b_lane)
+                    })
+                    .collect::<Vec<_>>();
+
+                // The products are summed pairwise like the hardware does, which matters for
+                // rounding
+                while terms.len() > 1 {
+                    terms =
+                        terms.chunks(2).map(|pair| fx.bcx.ins().fadd(pair[0], pair[1])).collect();
+                }
+                let sum = terms[0];
+
+                // The low four bits of the immediate select which lanes receive the sum, the rest
+                // are zeroed
+                for lane_idx in 0..lanes_per_half {
+                    let res_lane = if imm8 & (1 << lane_idx) != 0 { sum } else { zero };
+                    ret.place_lane(fx, half_base + lane_idx)
+                        .write_cvalue(fx, CValue::by_val(res_lane, lane_layout));
+                }
+            }
+        }
+        "llvm.x86.sse41.ptestz"
+        | "llvm.x86.sse41.ptestc"
+        | "llvm.x86.sse41.ptestnzc"
+        | "llvm.x86.avx.ptestz.256"
+        | "llvm.x86.avx.ptestc.256"
+        | "llvm.x86.avx.ptestnzc.256"
+        | "llvm.x86.avx.vtestz.ps"
+        | "llvm.x86.avx.vtestc.ps"
+        | "llvm.x86.avx.vtestnzc.ps"
+        | "llvm.x86.avx.vtestz.pd"
+        | "llvm.x86.avx.vtestc.pd"
+        | "llvm.x86.avx.vtestnzc.pd"
+        | "llvm.x86.avx.vtestz.ps.256"
+        | "llvm.x86.avx.vtestc.ps.256"
+        | "llvm.x86.avx.vtestnzc.ps.256"
+        | "llvm.x86.avx.vtestz.pd.256"
+        | "llvm.x86.avx.vtestc.pd.256"
+        | "llvm.x86.avx.vtestnzc.pd.256" => {
+            // https://www.felixcloutier.com/x86/ptest
+            // https://www.felixcloutier.com/x86/vtestpd:vtestps
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            assert_eq!(a.layout(), b.layout());
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_ty = fx.clif_type(lane_ty).unwrap();
+            let int_lane_ty = match lane_ty {
+                types::F32 => types::I32,
+                types::F64 => types::I64,
+                ty => ty,
+            };
+            let lane_as_int = |fx: &mut FunctionCx<'_, '_, 'tcx>, lane| {
+                if lane_ty.is_float() { codegen_bitcast(fx, int_lane_ty, lane) } else { lane }
+            };
+
+            // ZF is set when `a & b` is all zeros and CF is set when `!a & b` is all zeros
+            let mut and_acc = fx.bcx.ins().iconst(int_lane_ty, 0);
+            let mut andn_acc = fx.bcx.ins().iconst(int_lane_ty, 0);
+            for lane_idx in 0..lane_count {
+                let a_lane = a.value_lane(fx, lane_idx).load_scalar(fx);
+                let a_lane = lane_as_int(fx, a_lane);
+                let b_lane = b.value_lane(fx, lane_idx).load_scalar(fx);
+                let b_lane = lane_as_int(fx, b_lane);
+                let and = fx.bcx.ins().band(a_lane, b_lane);
+                and_acc = fx.bcx.ins().bor(and_acc, and);
+                let andn = fx.bcx.ins().band_not(b_lane, a_lane);
+                andn_acc = fx.bcx.ins().bor(andn_acc, andn);
+            }
+
+            // vtestps and vtestpd only consider the sign bit of every lane
+            if intrinsic.contains(".vtest") {
+                let sign_bit = i64::MIN >> (64 - int_lane_ty.bits());
+                and_acc = fx.bcx.ins().band_imm(and_acc, sign_bit);
+                andn_acc = fx.bcx.ins().band_imm(andn_acc, sign_bit);
+            }
+            let zf = fx.bcx.ins().icmp_imm(IntCC::Equal, and_acc, 0);
+            let cf = fx.bcx.ins().icmp_imm(IntCC::Equal, andn_acc, 0);
+
+            let res = if intrinsic.contains("testz") {
+                zf
+            } else if intrinsic.contains("testc") {
+                cf
+            } else {
+                let zf_or_cf = fx.bcx.ins().bor(zf, cf);
+                fx.bcx.ins().bxor_imm(zf_or_cf, 1)
+            };
+            let res = fx.bcx.ins().uextend(types::I32, res);
+            ret.write_cvalue(fx, CValue::by_val(res, fx.layout_of(fx.tcx.types.i32)));
+        }
+        "llvm.x86.sse42.crc32.32.8"
+        | "llvm.x86.sse42.crc32.32.16"
+        | "llvm.x86.sse42.crc32.32.32"
+        | "llvm.x86.sse42.crc32.64.64" => {
+            // https://www.felixcloutier.com/x86/crc32
+            intrinsic_args!(fx, args => (crc, v); intrinsic);
+
+            let crc = crc.load_scalar(fx);
+            let v = v.load_scalar(fx);
+
+            // Calculate in 64bit and truncate at the end, the result always fits in 32 bits
+            let crc_ty = fx.bcx.func.dfg.value_type(crc);
+            let crc =
+                if crc_ty != types::I64 { fx.bcx.ins().uextend(types::I64, crc) } else { crc };
+            let v_bits = fx.bcx.func.dfg.value_type(v).bits();
+            let v = if v_bits != 64 { fx.bcx.ins().uextend(types::I64, v) } else { v };
+
+            // Bitwise crc32c using the bit reflected polynomial
+            let mut res = fx.bcx.ins().bxor(crc, v);
+            for _ in 0..v_bits {
+                let lsb = fx.bcx.ins().band_imm(res, 1);
+                let mask = fx.bcx.ins().ineg(lsb);
+                let poly = fx.bcx.ins().band_imm(mask, 0x82f6_3b78);
+                let shifted = fx.bcx.ins().ushr_imm(res, 1);
+                res = fx.bcx.ins().bxor(shifted, poly);
+            }
+            let res = if crc_ty != types::I64 { fx.bcx.ins().ireduce(crc_ty, res) } else { res };
+
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+        }
+        "llvm.x86.pclmulqdq" => {
+            // https://www.felixcloutier.com/x86/pclmulqdq
+            let (a, b, imm8) = match args {
+                [a, b, imm8] => (a, b, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let imm8 = intrinsic_const_u8(fx, imm8, intrinsic, span);
+
+            let a_lane = a.value_lane(fx, u64::from(imm8 & 1)).load_scalar(fx);
+            let b_lane = b.value_lane(fx, u64::from((imm8 >> 4) & 1)).load_scalar(fx);
+
+            // Carry-less multiplication: xor together a shifted copy of `a` for every set bit of `b`
+            let a_wide = fx.bcx.ins().uextend(types::I128, a_lane);
+            let mut res = fx.bcx.ins().iconst(types::I64, 0);
+            res = fx.bcx.ins().uextend(types::I128, res);
+            for bit in 0..64 {
+                let b_bit = fx.bcx.ins().ushr_imm(b_lane, bit);
+                let b_bit = fx.bcx.ins().band_imm(b_bit, 1);
+                let mask = fx.bcx.ins().ineg(b_bit);
+                let mask = fx.bcx.ins().sextend(types::I128, mask);
+                let partial = fx.bcx.ins().ishl_imm(a_wide, bit);
+                let partial = fx.bcx.ins().band(partial, mask);
+                res = fx.bcx.ins().bxor(res, partial);
+            }
+
+            let lo = fx.bcx.ins().ireduce(types::I64, res);
+            let hi = fx.bcx.ins().ushr_imm(res, 64);
+            let hi = fx.bcx.ins().ireduce(types::I64, hi);
+
+            let lane_layout = ret.layout().field(fx, 0);
+            ret.place_lane(fx, 0).write_cvalue(fx, CValue::by_val(lo, lane_layout));
+            ret.place_lane(fx, 1).write_cvalue(fx, CValue::by_val(hi, lane_layout));
+        }
+        "llvm.x86.xsave"
+        | "llvm.x86.xsave64"
+        | "llvm.x86.xsaveopt"
+        | "llvm.x86.xsaveopt64"
+        | "llvm.x86.xsavec"
+        | "llvm.x86.xsavec64"
+        | "llvm.x86.xrstor"
+        | "llvm.x86.xrstor64" => {
+            // https://www.felixcloutier.com/x86/xsave
+            // https://www.felixcloutier.com/x86/xrstor
+            // The state components to save or restore are selected by the mask in edx:eax
+            intrinsic_args!(fx, args => (mem_addr, mask_hi, mask_lo); intrinsic);
+
+            if !crate::global_asm::asm_supported(fx.tcx) {
+                crate::trap::trap_unsupported_intrinsic(fx, intrinsic);
+                return;
+            }
+
+            let insn = intrinsic.strip_prefix("llvm.x86.").unwrap();
+            emit_native_insn(
+                fx,
+                format!("{insn} [rcx]"),
+                &[
+                    (X86InlineAsmReg::cx, mem_addr),
+                    (X86InlineAsmReg::dx, mask_hi),
+                    (X86InlineAsmReg::ax, mask_lo),
+                ],
+                &[],
+                &[],
+                InlineAsmOptions::empty(),
+            );
+        }
+        "llvm.x86.fxsave" | "llvm.x86.fxsave64" | "llvm.x86.fxrstor" | "llvm.x86.fxrstor64" => {
+            // https://www.felixcloutier.com/x86/fxsave
+            // https://www.felixcloutier.com/x86/fxrstor
+            intrinsic_args!(fx, args => (mem_addr); intrinsic);
+
+            if !crate::global_asm::asm_supported(fx.tcx) {
+                crate::trap::trap_unsupported_intrinsic(fx, intrinsic);
+                return;
+            }
+
+            // fxrstor only reads the memory at mem_addr, while fxsave writes to it
+            let options = if intrinsic.starts_with("llvm.x86.fxrstor") {
+                InlineAsmOptions::READONLY
+            } else {
+                InlineAsmOptions::empty()
+            };
+
+            let insn = intrinsic.strip_prefix("llvm.x86.").unwrap();
+            emit_native_insn(
+                fx,
+                format!("{insn} [rcx]"),
+                &[(X86InlineAsmReg::cx, mem_addr)],
+                &[],
+                &[],
+                options,
+            );
+        }
+        "llvm.x86.sse2.clflush" | "llvm.x86.clflushopt" | "llvm.x86.clwb" => {
+            // https://www.felixcloutier.com/x86/clflush
+            // https://www.felixcloutier.com/x86/clflushopt
+            // https://www.felixcloutier.com/x86/clwb
+            intrinsic_args!(fx, args => (p); intrinsic);
+
+            // Writing back cache lines has no effect observable by the program, so skipping the
+            // instruction is a valid fallback. The asm isn't marked pure or nomem to prevent it
+            // from being removed.
+            if crate::global_asm::asm_supported(fx.tcx) {
+                let insn = intrinsic.rsplit('.').next().unwrap();
+                emit_native_insn(
+                    fx,
+                    format!("{insn} byte ptr [rcx]"),
+                    &[(X86InlineAsmReg::cx, p)],
+                    &[],
+                    &[],
+                    InlineAsmOptions::empty(),
+                );
+            }
+        }
+        "llvm.x86.sse.stmxcsr" | "llvm.x86.sse.ldmxcsr" => {
+            // https://www.felixcloutier.com/x86/stmxcsr
+            // https://www.felixcloutier.com/x86/ldmxcsr
+            intrinsic_args!(fx, args => (mem_addr); intrinsic);
+
+            if !crate::global_asm::asm_supported(fx.tcx) {
+                crate::trap::trap_unsupported_intrinsic(fx, intrinsic);
+                return;
+            }
+
+            // ldmxcsr only reads the 32bit value at mem_addr, while stmxcsr writes to it
+            let options = if intrinsic == "llvm.x86.sse.ldmxcsr" {
+                InlineAsmOptions::READONLY
+            } else {
+                InlineAsmOptions::empty()
+            };
+
+            let insn = intrinsic.strip_prefix("llvm.x86.sse.").unwrap();
+            emit_native_insn(
+                fx,
+                format!("{insn} dword ptr [rcx]"),
+                &[(X86InlineAsmReg::cx, mem_addr)],
+                &[],
+                &[],
+                options,
+            );
+        }
+        "llvm.x86.sse3.monitor" => {
+            // https://www.felixcloutier.com/x86/monitor
+            intrinsic_args!(fx, args => (addr, extensions, hints); intrinsic);
+
+            if !crate::global_asm::asm_supported(fx.tcx) {
+                crate::trap::trap_unsupported_intrinsic(fx, intrinsic);
+                return;
+            }
+
+            emit_native_insn(
+                fx,
+                "monitor".to_string(),
+                &[
+                    (X86InlineAsmReg::ax, addr),
+                    (X86InlineAsmReg::cx, extensions),
+                    (X86InlineAsmReg::dx, hints),
+                ],
+                &[],
+                &[],
+                InlineAsmOptions::empty(),
+            );
+        }
+        "llvm.x86.sse3.mwait" => {
+            // https://www.felixcloutier.com/x86/mwait
+            intrinsic_args!(fx, args => (extensions, hints); intrinsic);
+
+            if !crate::global_asm::asm_supported(fx.tcx) {
+                crate::trap::trap_unsupported_intrinsic(fx, intrinsic);
+                return;
+            }
+
+            emit_native_insn(
+                fx,
+                "mwait".to_string(),
+                &[(X86InlineAsmReg::cx, extensions), (X86InlineAsmReg::ax, hints)],
+                &[],
+                &[],
+                InlineAsmOptions::empty(),
+            );
+        }
+        "llvm.x86.sse2.packsswb.128"
+        | "llvm.x86.sse2.packssdw.128"
+        | "llvm.x86.sse2.packuswb.128"
+        | "llvm.x86.sse41.packusdw"
+        | "llvm.x86.avx2.packsswb"
+        | "llvm.x86.avx2.packssdw"
+        | "llvm.x86.avx2.packuswb"
+        | "llvm.x86.avx2.packusdw" => {
+            // https://www.felixcloutier.com/x86/packsswb:packssdw
+            // https://www.felixcloutier.com/x86/packuswb
+            // https://www.felixcloutier.com/x86/packusdw
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            assert_eq!(a.layout(), b.layout());
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let (ret_lane_count, ret_lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            assert_eq!(lane_count * 2, ret_lane_count);
+            let lane_clif_ty = fx.clif_type(lane_ty).unwrap();
+            let ret_lane_layout = fx.layout_of(ret_lane_ty);
+            let ret_lane_clif_ty = fx.clif_type(ret_lane_ty).unwrap();
+
+            // packss* saturates to the signed and packus* to the unsigned range of the result
+            let ret_lane_bits = ret_lane_clif_ty.bits();
+            let (min, max) = if intrinsic.contains(".packss") {
+                (-(1i64 << (ret_lane_bits - 1)), (1i64 << (ret_lane_bits - 1)) - 1)
+            } else {
+                (0, (1i64 << ret_lane_bits) - 1)
+            };
+
+            // Every 128bit half is packed independently. Within a half the lanes of a form the
+            // low half of the result and the lanes of b the high half.
+            let lanes_per_half = 16 / fx.layout_of(lane_ty).size.bytes();
+            for half in 0..lane_count / lanes_per_half {
+                for (src_idx, src) in [a, b].into_iter().enumerate() {
+                    for idx in 0..lanes_per_half {
+                        let lane = src.value_lane(fx, half * lanes_per_half + idx).load_scalar(fx);
+
+                        let min = fx.bcx.ins().iconst(lane_clif_ty, min);
+                        let max = fx.bcx.ins().iconst(lane_clif_ty, max);
+                        let lane = fx.bcx.ins().smax(lane, min);
+                        let lane = fx.bcx.ins().smin(lane, max);
+
+                        let res_lane = fx.bcx.ins().ireduce(ret_lane_clif_ty, lane);
+                        let res_lane = CValue::by_val(res_lane, ret_lane_layout);
+                        let out_idx =
+                            half * lanes_per_half * 2 + src_idx as u64 * lanes_per_half + idx;
+                        ret.place_lane(fx, out_idx).write_cvalue(fx, res_lane);
+                    }
+                }
+            }
+        }
+        "llvm.x86.sse41.mpsadbw" | "llvm.x86.avx2.mpsadbw" => {
+            // https://www.felixcloutier.com/x86/mpsadbw
+            let (a, b, imm8) = match args {
+                [a, b, imm8] => (a, b, imm8),
+                _ => bug!("wrong number of args for intrinsic {intrinsic}"),
+            };
+            let a = codegen_operand(fx, a);
+            let b = codegen_operand(fx, b);
+            let imm8 = intrinsic_const_u8(fx, imm8, intrinsic, span);
+
+            assert_eq!(a.layout(), b.layout());
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let (ret_lane_count, ret_lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            assert_eq!(lane_ty, fx.tcx.types.u8);
+            assert_eq!(ret_lane_ty, fx.tcx.types.u16);
+            assert_eq!(lane_count, ret_lane_count * 2);
+            let ret_lane_layout = fx.layout_of(fx.tcx.types.u16);
+
+            // Every 128bit half is handled independently. The low half uses bits 0-2 of the
+            // immediate and the high half of the 256bit variant uses bits 3-5.
+            for half in 0..lane_count / 16 {
+                let half_imm = imm8 >> (half * 3);
+                let a_offset = half * 16 + u64::from((half_imm >> 2) & 1) * 4;
+                let b_offset = half * 16 + u64::from(half_imm & 3) * 4;
+
+                for out_idx in 0..8 {
+                    let sum = sad4(fx, a, a_offset + out_idx, b, b_offset);
+                    let res_lane = CValue::by_val(sum, ret_lane_layout);
+                    ret.place_lane(fx, half * 8 + out_idx).write_cvalue(fx, res_lane);
+                }
+            }
+        }
+        "llvm.x86.sse2.psad.bw" | "llvm.x86.avx2.psad.bw" => {
+            // https://www.felixcloutier.com/x86/psadbw
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            assert_eq!(a.layout(), b.layout());
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let (ret_lane_count, ret_lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            assert_eq!(lane_ty, fx.tcx.types.u8);
+            assert_eq!(ret_lane_ty, fx.tcx.types.u64);
+            assert_eq!(lane_count, ret_lane_count * 8);
+            let ret_lane_layout = fx.layout_of(fx.tcx.types.u64);
+
+            // The sum of the absolute differences of every group of 8 bytes is at most 8 * 255,
+            // so it is zero extended into the low 16 bits of the corresponding 64bit lane
+            for out_idx in 0..ret_lane_count {
+                let low = sad4(fx, a, out_idx * 8, b, out_idx * 8);
+                let high = sad4(fx, a, out_idx * 8 + 4, b, out_idx * 8 + 4);
+                let sum = fx.bcx.ins().iadd(low, high);
+                let sum = fx.bcx.ins().uextend(types::I64, sum);
+                ret.place_lane(fx, out_idx).write_cvalue(fx, CValue::by_val(sum, ret_lane_layout));
+            }
+        }
+        "llvm.x86.rdpmc" => {
+            // https://www.felixcloutier.com/x86/rdpmc
+            intrinsic_args!(fx, args => (counter); intrinsic);
+
+            if !crate::global_asm::asm_supported(fx.tcx) {
+                crate::trap::trap_unsupported_intrinsic(fx, intrinsic);
+                return;
+            }
+
+            let u32_layout = fx.layout_of(fx.tcx.types.u32);
+            let lo = CPlace::new_stack_slot(fx, u32_layout);
+            let hi = CPlace::new_stack_slot(fx, u32_layout);
+            emit_native_insn(
+                fx,
+                "rdpmc".to_string(),
+                &[(X86InlineAsmReg::cx, counter)],
+                &[(X86InlineAsmReg::ax, lo), (X86InlineAsmReg::dx, hi)],
+                &[],
+                InlineAsmOptions::NOMEM,
+            );
+
+            // The counter value is returned in edx:eax
+            let lo = lo.to_cvalue(fx).load_scalar(fx);
+            let hi = hi.to_cvalue(fx).load_scalar(fx);
+            let lo = fx.bcx.ins().uextend(types::I64, lo);
+            let hi = fx.bcx.ins().uextend(types::I64, hi);
+            let hi = fx.bcx.ins().ishl_imm(hi, 32);
+            let res = fx.bcx.ins().bor(hi, lo);
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+        }
+        "llvm.x86.rdpid" => {
+            // https://www.felixcloutier.com/x86/rdpid
+            intrinsic_args!(fx, args => (); intrinsic);
+
+            if !crate::global_asm::asm_supported(fx.tcx) {
+                crate::trap::trap_unsupported_intrinsic(fx, intrinsic);
+                return;
+            }
+
+            // rdpid writes a full 64bit register, but only the low 32 bits contain the processor id
+            emit_native_insn(
+                fx,
+                "rdpid rax".to_string(),
+                &[],
+                &[(X86InlineAsmReg::ax, ret)],
+                &[],
+                InlineAsmOptions::NOMEM,
+            );
+        }
         "llvm.x86.addcarry.64" => {
             intrinsic_args!(fx, args => (c_in, a, b); intrinsic);
             let c_in = c_in.load_scalar(fx);
 
-            llvm_add_sub(fx, BinOp::Add, ret, c_in, a, b);
+            if let Err(err) = llvm_add_sub(fx, BinOp::Add, ret, c_in, a, b) {
+                report_intrinsic_error(fx, intrinsic, span, err);
+                return;
+            }
         }
         "llvm.x86.subborrow.64" => {
             intrinsic_args!(fx, args => (b_in, a, b); intrinsic);
             let b_in = b_in.load_scalar(fx);
 
-            llvm_add_sub(fx, BinOp::Sub, ret, b_in, a, b);
+            if let Err(err) = llvm_add_sub(fx, BinOp::Sub, ret, b_in, a, b) {
+                report_intrinsic_error(fx, intrinsic, span, err);
+                return;
+            }
+        }
+        "llvm.x86.sse2.pmulh.w"
+        | "llvm.x86.sse2.pmulhu.w"
+        | "llvm.x86.avx2.pmulh.w"
+        | "llvm.x86.avx2.pmulhu.w" => {
+            // https://www.felixcloutier.com/x86/pmulhw
+            // https://www.felixcloutier.com/x86/pmulhuw
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            let signed = intrinsic.contains(".pmulh.");
+            simd_pair_for_each_lane(
+                fx,
+                a,
+                b,
+                ret,
+                &|fx, _lane_ty, _res_lane_ty, a_lane, b_lane| {
+                    // Only the high 16 bits of the 32bit product are kept
+                    let a_lane = clif_intcast(fx, a_lane, types::I32, signed);
+                    let b_lane = clif_intcast(fx, b_lane, types::I32, signed);
+                    let mul = fx.bcx.ins().imul(a_lane, b_lane);
+                    let high = fx.bcx.ins().ushr_imm(mul, 16);
+                    fx.bcx.ins().ireduce(types::I16, high)
+                },
+            );
+        }
+        "llvm.x86.ssse3.pmul.hr.sw.128" | "llvm.x86.avx2.pmul.hr.sw" => {
+            // https://www.felixcloutier.com/x86/pmulhrsw
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            simd_pair_for_each_lane(
+                fx,
+                a,
+                b,
+                ret,
+                &|fx, _lane_ty, _res_lane_ty, a_lane, b_lane| {
+                    // Round the 32bit product to nearest before keeping bits 16..=31 of it
+                    let a_lane = clif_intcast(fx, a_lane, types::I32, true);
+                    let b_lane = clif_intcast(fx, b_lane, types::I32, true);
+                    let mul = fx.bcx.ins().imul(a_lane, b_lane);
+                    let rounded = fx.bcx.ins().iadd_imm(mul, 0x4000);
+                    let high = fx.bcx.ins().sshr_imm(rounded, 15);
+                    fx.bcx.ins().ireduce(types::I16, high)
+                },
+            );
+        }
+        "llvm.x86.avx512.mask.compress.b.128"
+        | "llvm.x86.avx512.mask.compress.b.256"
+        | "llvm.x86.avx512.mask.compress.b.512"
+        | "llvm.x86.avx512.mask.compress.w.128"
+        | "llvm.x86.avx512.mask.compress.w.256"
+        | "llvm.x86.avx512.mask.compress.w.512"
+        | "llvm.x86.avx512.mask.compress.d.128"
+        | "llvm.x86.avx512.mask.compress.d.256"
+        | "llvm.x86.avx512.mask.compress.d.512"
+        | "llvm.x86.avx512.mask.compress.q.128"
+        | "llvm.x86.avx512.mask.compress.q.256"
+        | "llvm.x86.avx512.mask.compress.q.512"
+        | "llvm.x86.avx512.mask.compress.ps.128"
+        | "llvm.x86.avx512.mask.compress.ps.256"
+        | "llvm.x86.avx512.mask.compress.ps.512"
+        | "llvm.x86.avx512.mask.compress.pd.128"
+        | "llvm.x86.avx512.mask.compress.pd.256"
+        | "llvm.x86.avx512.mask.compress.pd.512" => {
+            // https://www.felixcloutier.com/x86/vpcompressb:vcompressw
+            // https://www.felixcloutier.com/x86/vpcompressd
+            // https://www.felixcloutier.com/x86/vpcompressq
+            // https://www.felixcloutier.com/x86/vcompressps
+            // https://www.felixcloutier.com/x86/vcompresspd
+            intrinsic_args!(fx, args => (a, src, mask); intrinsic);
+
+            let (lane_count, _lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_layout = ret.layout().field(fx, 0);
+            let mask = mask.load_scalar(fx);
+            let (mask_bits, prefix_counts) = mask_bits_and_prefix_counts(fx, mask, lane_count);
+
+            // The lanes selected by the mask are written contiguously starting at lane 0. Source
+            // lane i ends up in result lane j if it is selected and j lanes before it are too.
+            // The remaining lanes are taken from src.
+            for out_idx in 0..lane_count {
+                let mut res_lane = src.value_lane(fx, out_idx).load_scalar(fx);
+                for in_idx in out_idx..lane_count {
+                    let lane = a.value_lane(fx, in_idx).load_scalar(fx);
+                    let is_at_out_idx = fx.bcx.ins().icmp_imm(
+                        IntCC::Equal,
+                        prefix_counts[in_idx as usize],
+                        out_idx as i64,
+                    );
+                    let is_selected = fx.bcx.ins().band(is_at_out_idx, mask_bits[in_idx as usize]);
+                    res_lane = fx.bcx.ins().select(is_selected, lane, res_lane);
+                }
+                ret.place_lane(fx, out_idx).write_cvalue(fx, CValue::by_val(res_lane, lane_layout));
+            }
+        }
+        "llvm.x86.avx512.mask.expand.b.128"
+        | "llvm.x86.avx512.mask.expand.b.256"
+        | "llvm.x86.avx512.mask.expand.b.512"
+        | "llvm.x86.avx512.mask.expand.w.128"
+        | "llvm.x86.avx512.mask.expand.w.256"
+        | "llvm.x86.avx512.mask.expand.w.512"
+        | "llvm.x86.avx512.mask.expand.d.128"
+        | "llvm.x86.avx512.mask.expand.d.256"
+        | "llvm.x86.avx512.mask.expand.d.512"
+        | "llvm.x86.avx512.mask.expand.q.128"
+        | "llvm.x86.avx512.mask.expand.q.256"
+        | "llvm.x86.avx512.mask.expand.q.512"
+        | "llvm.x86.avx512.mask.expand.ps.128"
+        | "llvm.x86.avx512.mask.expand.ps.256"
+        | "llvm.x86.avx512.mask.expand.ps.512"
+        | "llvm.x86.avx512.mask.expand.pd.128"
+        | "llvm.x86.avx512.mask.expand.pd.256"
+        | "llvm.x86.avx512.mask.expand.pd.512" => {
+            // https://www.felixcloutier.com/x86/vpexpandb:vpexpandw
+            // https://www.felixcloutier.com/x86/vpexpandd
+            // https://www.felixcloutier.com/x86/vpexpandq
+            // https://www.felixcloutier.com/x86/vexpandps
+            // https://www.felixcloutier.com/x86/vexpandpd
+            intrinsic_args!(fx, args => (a, src, mask); intrinsic);
+
+            let (lane_count, _lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            let lane_layout = ret.layout().field(fx, 0);
+            let mask = mask.load_scalar(fx);
+            let (mask_bits, prefix_counts) = mask_bits_and_prefix_counts(fx, mask, lane_count);
+
+            // The contiguous lanes starting at lane 0 are read into the lanes selected by the
+            // mask. Result lane i reads the source lane counting the selected lanes before it.
+            // The remaining lanes are taken from src.
+            for out_idx in 0..lane_count {
+                let src_lane = src.value_lane(fx, out_idx).load_scalar(fx);
+                let lane =
+                    select_lane_dynamic(fx, a, 0, out_idx + 1, prefix_counts[out_idx as usize]);
+                let res_lane = fx.bcx.ins().select(mask_bits[out_idx as usize], lane, src_lane);
+                ret.place_lane(fx, out_idx).write_cvalue(fx, CValue::by_val(res_lane, lane_layout));
+            }
+        }
+        "llvm.x86.sse2.pmadd.wd" | "llvm.x86.avx2.pmadd.wd" => {
+            // https://www.felixcloutier.com/x86/pmaddwd
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            simd_adjacent_pairs(fx, a, b, ret, &|fx, a0, a1, b0, b1| {
+                // Only (-0x8000 * -0x8000) * 2 overflows, which wraps to i32::MIN like on x86
+                let [a0, a1, b0, b1] =
+                    [a0, a1, b0, b1].map(|lane| fx.bcx.ins().sextend(types::I32, lane));
+                let mul0 = fx.bcx.ins().imul(a0, b0);
+                let mul1 = fx.bcx.ins().imul(a1, b1);
+                fx.bcx.ins().iadd(mul0, mul1)
+            });
+        }
+        "llvm.x86.ssse3.pmadd.ub.sw.128" | "llvm.x86.avx2.pmadd.ub.sw" => {
+            // https://www.felixcloutier.com/x86/pmaddubsw
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            simd_adjacent_pairs(fx, a, b, ret, &|fx, a0, a1, b0, b1| {
+                // The lanes of a are unsigned and the lanes of b are signed
+                let [a0, a1] = [a0, a1].map(|lane| fx.bcx.ins().uextend(types::I32, lane));
+                let [b0, b1] = [b0, b1].map(|lane| fx.bcx.ins().sextend(types::I32, lane));
+                let mul0 = fx.bcx.ins().imul(a0, b0);
+                let mul1 = fx.bcx.ins().imul(a1, b1);
+                let sum = fx.bcx.ins().iadd(mul0, mul1);
+
+                // The sum is saturated to the i16 range
+                let min = fx.bcx.ins().iconst(types::I32, i64::from(i16::MIN));
+                let max = fx.bcx.ins().iconst(types::I32, i64::from(i16::MAX));
+                let sum = fx.bcx.ins().smax(sum, min);
+                let sum = fx.bcx.ins().smin(sum, max);
+                fx.bcx.ins().ireduce(types::I16, sum)
+            });
+        }
+        "llvm.x86.sse2.pmulu.dq"
+        | "llvm.x86.avx2.pmulu.dq"
+        | "llvm.x86.sse41.pmuldq"
+        | "llvm.x86.avx2.pmul.dq" => {
+            // https://www.felixcloutier.com/x86/pmuludq
+            // https://www.felixcloutier.com/x86/pmuldq
+            intrinsic_args!(fx, args => (a, b); intrinsic);
+
+            let signed = matches!(intrinsic, "llvm.x86.sse41.pmuldq" | "llvm.x86.avx2.pmul.dq");
+            let (in_lane_ty, out_lane_ty) = if signed {
+                (fx.tcx.types.i32, fx.tcx.types.i64)
+            } else {
+                (fx.tcx.types.u32, fx.tcx.types.u64)
+            };
+
+            let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+            assert_eq!(lane_ty, in_lane_ty);
+            let (ret_lane_count, ret_lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+            assert_eq!(ret_lane_ty, out_lane_ty);
+            assert_eq!(lane_count, ret_lane_count * 2);
+
+            let ret_lane_layout = fx.layout_of(ret_lane_ty);
+            for out_lane_idx in 0..ret_lane_count {
+                // Only the even (low) 32bit lane of every 64bit lane participates
+                let a_lane = a.value_lane(fx, out_lane_idx * 2).load_scalar(fx);
+                let a_lane = clif_intcast(fx, a_lane, types::I64, signed);
+                let b_lane = b.value_lane(fx, out_lane_idx * 2).load_scalar(fx);
+                let b_lane = clif_intcast(fx, b_lane, types::I64, signed);
+
+                let res_lane = fx.bcx.ins().imul(a_lane, b_lane);
+                let res_lane = CValue::by_val(res_lane, ret_lane_layout);
+                ret.place_lane(fx, out_lane_idx).write_cvalue(fx, res_lane);
+            }
         }
         _ => {
             fx.tcx
                 .sess
                 .warn(format!("unsupported x86 llvm intrinsic {}; replacing with trap", intrinsic));
-            crate::trap::trap_unimplemented(fx, intrinsic);
+            crate::trap::trap_unsupported_intrinsic(fx, intrinsic);
             return;
         }
     }
@@ -157,6 +1502,271 @@ pub(crate) fn codegen_x86_llvm_intrinsic_call<'tcx>(
 // llvm.x86.avx2.psrli.w
 // llvm.x86.sse2.psrli.w
 
+/// A malformed call to an llvm intrinsic. These can only be caused by calling the intrinsic
+/// directly rather than through `core::arch`, so they are reported as an error instead of causing
+/// an ICE.
+enum IntrinsicError<'tcx> {
+    /// The comparison predicate immediate is larger than the largest AVX predicate.
+    InvalidCmpKind(u8),
+    /// Operand `idx` has a different type than the intrinsic expects.
+    OperandTypeMismatch { idx: usize, expected: Ty<'tcx>, found: Ty<'tcx> },
+}
+
+/// Emit a single native instruction using inline asm. The registers of `inputs` are initialized
+/// with the given values before the instruction runs. The registers of `outputs` are written to the
+/// given places afterwards, while those of `clobbers` are only marked as overwritten. Memory is
+/// assumed to be read and written unless `options` says otherwise. `NOSTACK` is always added as
+/// none of the instructions emitted this way touch the stack.
+///
+/// The caller is responsible for checking `crate::global_asm::asm_supported` first.
+fn emit_native_insn<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    template: String,
+    inputs: &[(X86InlineAsmReg, CValue<'tcx>)],
+    outputs: &[(X86InlineAsmReg, CPlace<'tcx>)],
+    clobbers: &[X86InlineAsmReg],
+    options: InlineAsmOptions,
+) {
+    let reg = |reg| InlineAsmRegOrRegClass::Reg(InlineAsmReg::X86(reg));
+    let operands = inputs
+        .iter()
+        .map(|&(r, value)| CInlineAsmOperand::In { reg: reg(r), value })
+        .chain(outputs.iter().map(|&(r, place)| CInlineAsmOperand::Out {
+            reg: reg(r),
+            late: true,
+            place: Some(place),
+        }))
+        .chain(clobbers.iter().map(|&r| CInlineAsmOperand::Out {
+            reg: reg(r),
+            late: true,
+            place: None,
+        }))
+        .collect::<Vec<_>>();
+
+    codegen_inline_asm_inner(
+        fx,
+        &[InlineAsmTemplatePiece::String(template)],
+        &operands,
+        options | InlineAsmOptions::NOSTACK,
+    );
+}
+
+fn report_intrinsic_error(
+    fx: &mut FunctionCx<'_, '_, '_>,
+    intrinsic: &str,
+    span: Span,
+    err: IntrinsicError<'_>,
+) {
+    let msg = match err {
+        IntrinsicError::InvalidCmpKind(kind) => format!("invalid comparison predicate {kind}"),
+        IntrinsicError::OperandTypeMismatch { idx, expected, found } => {
+            format!("expected operand {idx} to be of type `{expected}`, found `{found}`")
+        }
+    };
+    fx.tcx.sess.span_err(span, format!("invalid call to `{intrinsic}`: {msg}"));
+    // Prevent verifier error
+    fx.bcx.ins().trap(TrapCode::UnreachableCodeReached);
+}
+
+/// The comparison selected by the predicate immediate of the `cmp{ps,pd,ss,sd}` intrinsics.
+#[derive(Copy, Clone)]
+enum FloatCmp {
+    Cc(FloatCC),
+    /// The `FALSE_*` and `TRUE_*` predicates don't depend on the operands
+    Const(bool),
+}
+
+/// Map the comparison predicate immediate of the `cmp{ps,pd,ss,sd}` intrinsics to a `FloatCmp`.
+/// SSE only has predicates 0 to 7, AVX extends them to 32.
+fn cmp_kind_to_float_cmp(kind: u8) -> Result<FloatCmp, IntrinsicError<'static>> {
+    if kind > 31 {
+        return Err(IntrinsicError::InvalidCmpKind(kind));
+    }
+    // Predicates 16 to 31 only differ from 0 to 15 in whether quiet NaNs raise an invalid operation
+    // exception, which isn't emulated
+    Ok(FloatCmp::Cc(match kind & 0xf {
+        0 => FloatCC::Equal,                         // EQ_OQ
+        1 => FloatCC::LessThan,                      // LT_OS
+        2 => FloatCC::LessThanOrEqual,               // LE_OS
+        3 => FloatCC::Unordered,                     // UNORD_Q
+        4 => FloatCC::NotEqual,                      // NEQ_UQ
+        5 => FloatCC::UnorderedOrGreaterThanOrEqual, // NLT_US
+        6 => FloatCC::UnorderedOrGreaterThan,        // NLE_US
+        7 => FloatCC::Ordered,                       // ORD_Q
+        8 => FloatCC::UnorderedOrEqual,              // EQ_UQ
+        9 => FloatCC::UnorderedOrLessThan,           // NGE_US
+        10 => FloatCC::UnorderedOrLessThanOrEqual,   // NGT_US
+        11 => return Ok(FloatCmp::Const(false)),     // FALSE_OQ
+        12 => FloatCC::OrderedNotEqual,              // NEQ_OQ
+        13 => FloatCC::GreaterThanOrEqual,           // GE_OS
+        14 => FloatCC::GreaterThan,                  // GT_OS
+        15 => return Ok(FloatCmp::Const(true)),      // TRUE_UQ
+        _ => unreachable!(),
+    }))
+}
+
+/// Compare `x` and `y` like the `cmp{ps,pd,ss,sd}` instructions, returning a boolean.
+fn x86_float_cmp(fx: &mut FunctionCx<'_, '_, '_>, cmp: FloatCmp, x: Value, y: Value) -> Value {
+    match cmp {
+        FloatCmp::Cc(cc) => fx.bcx.ins().fcmp(cc, x, y),
+        FloatCmp::Const(res) => fx.bcx.ins().iconst(types::I8, i64::from(res)),
+    }
+}
+
+/// The x86 float min and max instructions return the second operand when the operands compare
+/// unordered or equal. This differs from `fmin`/`fmax` for NaN and for `0.0` vs `-0.0`.
+fn x86_float_min_max(fx: &mut FunctionCx<'_, '_, '_>, is_max: bool, x: Value, y: Value) -> Value {
+    let cc = if is_max { FloatCC::GreaterThan } else { FloatCC::LessThan };
+    let use_x = fx.bcx.ins().fcmp(cc, x, y);
+    fx.bcx.ins().select(use_x, x, y)
+}
+
+/// Compute the exact value approximated by `rcpps` (`1 / x`) or `rsqrtps` (`1 / sqrt(x)`).
+fn x86_rcp_rsqrt(fx: &mut FunctionCx<'_, '_, '_>, is_rsqrt: bool, lane: Value) -> Value {
+    let lane = if is_rsqrt { fx.bcx.ins().sqrt(lane) } else { lane };
+    let one = fx.bcx.ins().f32const(1.0);
+    fx.bcx.ins().fdiv(one, lane)
+}
+
+/// Round a float like the SSE4.1 round instructions do for the given rounding immediate. Bits 0-1
+/// select the rounding mode unless bit 2 requests the current MXCSR rounding mode, which is
+/// assumed to be the default of round to nearest even. Bit 3 only suppresses the precision
+/// exception and doesn't affect the result.
+fn x86_round(fx: &mut FunctionCx<'_, '_, '_>, lane: Value, rounding: u8) -> Value {
+    if rounding & 0b100 != 0 {
+        return fx.bcx.ins().nearest(lane);
+    }
+    match rounding & 0b11 {
+        0 => fx.bcx.ins().nearest(lane),
+        1 => fx.bcx.ins().floor(lane),
+        2 => fx.bcx.ins().ceil(lane),
+        3 => fx.bcx.ins().trunc(lane),
+        _ => unreachable!(),
+    }
+}
+
+/// Convert a float to an `i32` or `i64` like the x86 conversion instructions do. NaN and out of
+/// range inputs result in the "integer indefinite" value `iN::MIN` rather than saturating. The
+/// non-truncating conversions round to nearest even, which is the default MXCSR rounding mode.
+fn float_to_int_or_indefinite(
+    fx: &mut FunctionCx<'_, '_, '_>,
+    lane: Value,
+    int_ty: Type,
+    truncate: bool,
+) -> Value {
+    let rounded = if truncate { fx.bcx.ins().trunc(lane) } else { fx.bcx.ins().nearest(lane) };
+
+    // Powers of two are exactly representable in both float types
+    let bound = (1u64 << (int_ty.bits() - 1)) as f64;
+    let (min, max_exclusive) = match fx.bcx.func.dfg.value_type(lane) {
+        types::F32 => (fx.bcx.ins().f32const(-bound as f32), fx.bcx.ins().f32const(bound as f32)),
+        types::F64 => (fx.bcx.ins().f64const(-bound), fx.bcx.ins().f64const(bound)),
+        ty => unreachable!("{:?}", ty),
+    };
+    // Both comparisons are false for NaN
+    let ge_min = fx.bcx.ins().fcmp(FloatCC::GreaterThanOrEqual, rounded, min);
+    let lt_max = fx.bcx.ins().fcmp(FloatCC::LessThan, rounded, max_exclusive);
+    let in_range = fx.bcx.ins().band(ge_min, lt_max);
+
+    let res = fx.bcx.ins().fcvt_to_sint_sat(int_ty, rounded);
+    let indefinite = fx.bcx.ins().iconst(int_ty, i64::MIN >> (64 - int_ty.bits()));
+    fx.bcx.ins().select(in_range, res, indefinite)
+}
+
+/// Combine adjacent lane pairs of `a` and `b` like the x86 horizontal add and subtract
+/// instructions. Every 128bit half of the result is formed from the pairs of the corresponding half
+/// of `a` followed by the pairs of the corresponding half of `b`.
+fn simd_horizontal_pair<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    a: CValue<'tcx>,
+    b: CValue<'tcx>,
+    ret: CPlace<'tcx>,
+    f: &dyn Fn(&mut FunctionCx<'_, '_, 'tcx>, Value, Value) -> Value,
+) {
+    assert_eq!(a.layout(), b.layout());
+    assert_eq!(a.layout(), ret.layout());
+    let (lane_count, lane_ty) = a.layout().ty.simd_size_and_type(fx.tcx);
+    let lane_layout = fx.layout_of(lane_ty);
+
+    let lanes_per_half = 16 / lane_layout.size.bytes();
+    let pairs_per_half = lanes_per_half / 2;
+    for half in 0..lane_count / lanes_per_half {
+        let half_base = half * lanes_per_half;
+        for (src_idx, src) in [a, b].into_iter().enumerate() {
+            for pair in 0..pairs_per_half {
+                let x_lane = src.value_lane(fx, half_base + pair * 2).load_scalar(fx);
+                let y_lane = src.value_lane(fx, half_base + pair * 2 + 1).load_scalar(fx);
+                let res_lane = CValue::by_val(f(fx, x_lane, y_lane), lane_layout);
+                let out_idx = half_base + src_idx as u64 * pairs_per_half + pair;
+                ret.place_lane(fx, out_idx).write_cvalue(fx, res_lane);
+            }
+        }
+    }
+}
+
+/// Read lane `lane_base + idx` of `val` where `idx` is only known at runtime and must be less than
+/// `lane_count`.
+fn select_lane_dynamic<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    val: CValue<'tcx>,
+    lane_base: u64,
+    lane_count: u64,
+    idx: Value,
+) -> Value {
+    let mut res = val.value_lane(fx, lane_base).load_scalar(fx);
+    for lane_idx in 1..lane_count {
+        let lane = val.value_lane(fx, lane_base + lane_idx).load_scalar(fx);
+        let is_lane = fx.bcx.ins().icmp_imm(IntCC::Equal, idx, lane_idx as i64);
+        res = fx.bcx.ins().select(is_lane, lane, res);
+    }
+    res
+}
+
+/// Split `mask` into a boolean per lane for the first `lane_count` lanes. Also returns the number
+/// of bits set below each lane.
+fn mask_bits_and_prefix_counts(
+    fx: &mut FunctionCx<'_, '_, '_>,
+    mask: Value,
+    lane_count: u64,
+) -> (Vec<Value>, Vec<Value>) {
+    let mask_ty = fx.bcx.func.dfg.value_type(mask);
+    let mut mask_bits = Vec::with_capacity(lane_count as usize);
+    let mut prefix_counts = Vec::with_capacity(lane_count as usize);
+    let mut count = fx.bcx.ins().iconst(mask_ty, 0);
+    for lane_idx in 0..lane_count {
+        let bit = fx.bcx.ins().ushr_imm(mask, lane_idx as i64);
+        let bit = fx.bcx.ins().band_imm(bit, 1);
+        prefix_counts.push(count);
+        count = fx.bcx.ins().iadd(count, bit);
+        mask_bits.push(fx.bcx.ins().icmp_imm(IntCC::NotEqual, bit, 0));
+    }
+    (mask_bits, prefix_counts)
+}
+
+/// Sum the absolute differences of the 4 unsigned bytes of `a` starting at lane `a_offset` and of
+/// `b` starting at lane `b_offset` as used by `psadbw` and `mpsadbw`. The result is an `i16` of at
+/// most 4 * 255.
+fn sad4<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    a: CValue<'tcx>,
+    a_offset: u64,
+    b: CValue<'tcx>,
+    b_offset: u64,
+) -> Value {
+    let mut sum = fx.bcx.ins().iconst(types::I16, 0);
+    for i in 0..4 {
+        let a_lane = a.value_lane(fx, a_offset + i).load_scalar(fx);
+        let a_lane = fx.bcx.ins().uextend(types::I16, a_lane);
+        let b_lane = b.value_lane(fx, b_offset + i).load_scalar(fx);
+        let b_lane = fx.bcx.ins().uextend(types::I16, b_lane);
+
+        let diff = fx.bcx.ins().isub(a_lane, b_lane);
+        let abs_diff = fx.bcx.ins().iabs(diff);
+        sum = fx.bcx.ins().iadd(sum, abs_diff);
+    }
+    sum
+}
+
 fn llvm_add_sub<'tcx>(
     fx: &mut FunctionCx<'_, '_, 'tcx>,
     bin_op: BinOp,
@@ -164,17 +1774,16 @@ fn llvm_add_sub<'tcx>(
     cb_in: Value,
     a: CValue<'tcx>,
     b: CValue<'tcx>,
-) {
-    assert_eq!(
-        a.layout().ty,
-        fx.tcx.types.u64,
-        "llvm.x86.addcarry.64/llvm.x86.subborrow.64 second operand must be u64"
-    );
-    assert_eq!(
-        b.layout().ty,
-        fx.tcx.types.u64,
-        "llvm.x86.addcarry.64/llvm.x86.subborrow.64 third operand must be u64"
-    );
+) -> Result<(), IntrinsicError<'tcx>> {
+    for (idx, operand) in [(1, a), (2, b)] {
+        if operand.layout().ty != fx.tcx.types.u64 {
+            return Err(IntrinsicError::OperandTypeMismatch {
+                idx,
+                expected: fx.tcx.types.u64,
+                found: operand.layout().ty,
+            });
+        }
+    }
 
     // c + carry -> c + first intermediate carry or borrow respectively
     let int0 = crate::num::codegen_checked_int_binop(fx, bin_op, a, b);
@@ -193,4 +1802,6 @@ fn llvm_add_sub<'tcx>(
     let layout = fx.layout_of(fx.tcx.mk_tup(&[fx.tcx.types.u8, fx.tcx.types.u64]));
     let val = CValue::by_val_pair(cb_out, c, layout);
     ret.write_cvalue(fx, val);
+
+    Ok(())
 }
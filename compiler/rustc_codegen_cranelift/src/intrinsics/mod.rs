@@ -28,7 +28,7 @@ macro_rules! intrinsic_args {
 use rustc_span::symbol::{kw, sym, Symbol};
 
 use crate::prelude::*;
-use cranelift_codegen::ir::AtomicRmwOp;
+use cranelift_codegen::ir::{AtomicRmwOp, Opcode};
 
 fn bug_on_incorrect_arg_count(intrinsic: impl std::fmt::Display) -> ! {
     bug!("wrong number of args for intrinsic {}", intrinsic);
@@ -51,6 +51,25 @@ fn report_atomic_type_validation_error<'tcx>(
     fx.bcx.ins().trap(TrapCode::UnreachableCodeReached);
 }
 
+/// Read the immediate argument `op` of `intrinsic` as `u8`.
+///
+/// Emits an error and returns 0 if `op` isn't a constant, which can happen when an llvm intrinsic
+/// is called directly from generic code.
+pub(crate) fn intrinsic_const_u8<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    op: &mir::Operand<'tcx>,
+    intrinsic: &str,
+    span: Span,
+) -> u8 {
+    let Some(imm) = crate::constant::mir_operand_get_const_val(fx, op)
+        .and_then(|imm| imm.try_to_scalar_int())
+    else {
+        fx.tcx.sess.span_err(span, format!("expected a constant argument to {intrinsic}"));
+        return 0;
+    };
+    imm.assert_bits(imm.size()) as u8
+}
+
 pub(crate) fn clif_vector_type<'tcx>(tcx: TyCtxt<'tcx>, layout: TyAndLayout<'tcx>) -> Type {
     let (element, count) = match layout.abi {
         Abi::Vector { element, count } => (element, count),
@@ -135,6 +154,87 @@ fn simd_pair_for_each_lane<'tcx>(
     }
 }
 
+/// Combine adjacent lanes of `x` and `y` into a single lane of `ret`, which has half as many lanes
+/// as the inputs. Result lane `i` is computed by `f` from lanes `2 * i` and `2 * i + 1` of both
+/// inputs, passed in the order `x[2 * i], x[2 * i + 1], y[2 * i], y[2 * i + 1]`. The lane types of
+/// `x` and `y` may differ in signedness.
+fn simd_adjacent_pairs<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    x: CValue<'tcx>,
+    y: CValue<'tcx>,
+    ret: CPlace<'tcx>,
+    f: &dyn Fn(&mut FunctionCx<'_, '_, 'tcx>, Value, Value, Value, Value) -> Value,
+) {
+    let (lane_count, _lane_ty) = x.layout().ty.simd_size_and_type(fx.tcx);
+    let (y_lane_count, _y_lane_ty) = y.layout().ty.simd_size_and_type(fx.tcx);
+    let (ret_lane_count, ret_lane_ty) = ret.layout().ty.simd_size_and_type(fx.tcx);
+    let ret_lane_layout = fx.layout_of(ret_lane_ty);
+    assert_eq!(lane_count, y_lane_count);
+    assert_eq!(lane_count, ret_lane_count * 2);
+
+    for out_idx in 0..ret_lane_count {
+        let x0 = x.value_lane(fx, out_idx * 2).load_scalar(fx);
+        let x1 = x.value_lane(fx, out_idx * 2 + 1).load_scalar(fx);
+        let y0 = y.value_lane(fx, out_idx * 2).load_scalar(fx);
+        let y1 = y.value_lane(fx, out_idx * 2 + 1).load_scalar(fx);
+
+        let res_lane = f(fx, x0, x1, y0, y1);
+        let res_lane = CValue::by_val(res_lane, ret_lane_layout);
+
+        ret.place_lane(fx, out_idx).write_cvalue(fx, res_lane);
+    }
+}
+
+/// Whether `op` on `vector_ty` can be lowered to a single vector instruction for the current
+/// target rather than requiring the vector to be split into lanes.
+fn has_native_vector_binop(fx: &FunctionCx<'_, '_, '_>, vector_ty: Type, op: Opcode) -> bool {
+    // Cranelift only supports 128bit vectors and only on these architectures
+    if vector_ty.bits() != 128
+        || !matches!(&*fx.tcx.sess.target.arch, "x86_64" | "aarch64" | "s390x")
+    {
+        return false;
+    }
+
+    if vector_ty.lane_type().is_float() {
+        matches!(op, Opcode::Fadd | Opcode::Fsub | Opcode::Fmul | Opcode::Fdiv)
+    } else {
+        // Integer multiplication isn't included as it needs sse4.1 on x86_64 for most lane types
+        matches!(op, Opcode::Iadd | Opcode::Isub | Opcode::Band | Opcode::Bor | Opcode::Bxor)
+    }
+}
+
+/// Apply the binary instruction `op` lane-wise to `x` and `y`. This uses a single vector
+/// instruction when the target supports it and falls back to applying `op` to each lane
+/// individually otherwise.
+fn simd_lanewise_binop<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    x: CValue<'tcx>,
+    y: CValue<'tcx>,
+    ret: CPlace<'tcx>,
+    op: Opcode,
+) {
+    assert_eq!(x.layout(), y.layout());
+    assert_eq!(x.layout(), ret.layout());
+
+    if let Abi::Vector { .. } = x.layout().abi {
+        let vector_ty = clif_vector_type(fx.tcx, x.layout());
+        if has_native_vector_binop(fx, vector_ty, op) {
+            let x = x.load_scalar(fx);
+            let y = y.load_scalar(fx);
+            let (inst, _) = fx.bcx.ins().Binary(op, vector_ty, x, y);
+            let res = fx.bcx.inst_results(inst)[0];
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
+            return;
+        }
+    }
+
+    simd_pair_for_each_lane(fx, x, y, ret, &|fx, _lane_ty, _ret_lane_ty, x_lane, y_lane| {
+        let lane_ty = fx.bcx.func.dfg.value_type(x_lane);
+        let (inst, _) = fx.bcx.ins().Binary(op, lane_ty, x_lane, y_lane);
+        fx.bcx.inst_results(inst)[0]
+    });
+}
+
 fn simd_reduce<'tcx>(
     fx: &mut FunctionCx<'_, '_, 'tcx>,
     val: CValue<'tcx>,
@@ -156,23 +256,35 @@ fn simd_reduce<'tcx>(
     ret.write_cvalue(fx, res);
 }
 
-// FIXME move all uses to `simd_reduce`
+/// Reduce a mask vector to a single `bool` which is true if all (`all == true`) or any
+/// (`all == false`) lanes are set. The lanes are combined as a balanced tree rather than one after
+/// another to shorten the dependency chain.
 fn simd_reduce_bool<'tcx>(
     fx: &mut FunctionCx<'_, '_, 'tcx>,
     val: CValue<'tcx>,
     ret: CPlace<'tcx>,
-    f: &dyn Fn(&mut FunctionCx<'_, '_, 'tcx>, Value, Value) -> Value,
+    all: bool,
 ) {
     let (lane_count, _lane_ty) = val.layout().ty.simd_size_and_type(fx.tcx);
     assert!(ret.layout().ty.is_bool());
 
-    let res_val = val.value_lane(fx, 0).load_scalar(fx);
-    let mut res_val = fx.bcx.ins().band_imm(res_val, 1); // mask to boolean
-    for lane_idx in 1..lane_count {
-        let lane = val.value_lane(fx, lane_idx).load_scalar(fx);
-        let lane = fx.bcx.ins().band_imm(lane, 1); // mask to boolean
-        res_val = f(fx, res_val, lane);
+    let mut lanes = (0..lane_count)
+        .map(|lane_idx| val.value_lane(fx, lane_idx).load_scalar(fx))
+        .collect::<Vec<_>>();
+    while lanes.len() > 1 {
+        lanes = lanes
+            .chunks(2)
+            .map(|pair| match *pair {
+                [a, b] if all => fx.bcx.ins().band(a, b),
+                [a, b] => fx.bcx.ins().bor(a, b),
+                [a] => a,
+                _ => unreachable!(),
+            })
+            .collect();
     }
+
+    // band and bor act on every bit independently, so masking to a boolean is only necessary once
+    let res_val = fx.bcx.ins().band_imm(lanes[0], 1);
     let res_val = if fx.bcx.func.dfg.value_type(res_val) != types::I8 {
         fx.bcx.ins().ireduce(types::I8, res_val)
     } else {
@@ -182,12 +294,14 @@ fn simd_reduce_bool<'tcx>(
     ret.write_cvalue(fx, res);
 }
 
-fn bool_to_zero_or_max_uint<'tcx>(
+/// Turn a comparison result into an all-zeros or all-ones lane of the given layout. For float lanes
+/// the all-ones integer mask is reinterpreted as float bit pattern.
+fn bool_to_lane_mask<'tcx>(
     fx: &mut FunctionCx<'_, '_, 'tcx>,
-    ty: Ty<'tcx>,
+    lane_layout: TyAndLayout<'tcx>,
     val: Value,
 ) -> Value {
-    let ty = fx.clif_type(ty).unwrap();
+    let ty = fx.clif_type(lane_layout.ty).unwrap();
 
     let int_ty = match ty {
         types::F32 => types::I32,
@@ -547,7 +661,8 @@ fn codegen_regular_intrinsic_call<'tcx>(
             intrinsic_args!(fx, args => (ptr, mask); intrinsic);
             let ptr = ptr.load_scalar(fx);
             let mask = mask.load_scalar(fx);
-            fx.bcx.ins().band(ptr, mask);
+            let res = fx.bcx.ins().band(ptr, mask);
+            ret.write_cvalue(fx, CValue::by_val(res, ret.layout()));
         }
 
         sym::write_bytes | sym::volatile_set_memory => {
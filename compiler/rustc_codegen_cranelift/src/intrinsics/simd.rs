@@ -18,6 +18,48 @@ fn report_simd_type_validation_error(
     fx.bcx.ins().trap(TrapCode::UnreachableCodeReached);
 }
 
+/// Read the constant lane index argument of `simd_insert` and `simd_extract`. If the index isn't a
+/// constant an error is reported and `None` is returned.
+fn simd_const_lane_index<'tcx>(
+    fx: &mut FunctionCx<'_, '_, 'tcx>,
+    intrinsic: Symbol,
+    span: Span,
+    idx: &mir::Operand<'tcx>,
+) -> Option<u128> {
+    let idx = crate::constant::mir_operand_get_const_val(fx, idx)
+        .and_then(|idx| idx.try_to_bits(Size::from_bytes(4 /* u32*/)));
+    if idx.is_none() {
+        fx.tcx.sess.span_err(
+            span,
+            format!(
+                "invalid monomorphization of `{}` intrinsic: the lane index must be a constant",
+                intrinsic
+            ),
+        );
+        // Prevent verifier error
+        fx.bcx.ins().trap(TrapCode::UnreachableCodeReached);
+    }
+    idx
+}
+
+fn report_simd_lane_index_out_of_bounds(
+    fx: &mut FunctionCx<'_, '_, '_>,
+    intrinsic: Symbol,
+    span: Span,
+    idx: u128,
+    lane_count: u64,
+) {
+    fx.tcx.sess.span_err(
+        span,
+        format!(
+            "invalid monomorphization of `{}` intrinsic: lane index {} is out of bounds (limit {})",
+            intrinsic, idx, lane_count
+        ),
+    );
+    // Prevent verifier error
+    fx.bcx.ins().trap(TrapCode::UnreachableCodeReached);
+}
+
 pub(super) fn codegen_simd_intrinsic_call<'tcx>(
     fx: &mut FunctionCx<'_, '_, 'tcx>,
     intrinsic: Symbol,
@@ -113,7 +155,8 @@ pub(super) fn codegen_simd_intrinsic_call<'tcx>(
                     _ => unreachable!(),
                 };
 
-                bool_to_zero_or_max_uint(fx, res_lane_ty, res_lane)
+                let res_lane_layout = fx.layout_of(res_lane_ty);
+                bool_to_lane_mask(fx, res_lane_layout, res_lane)
             });
         }
 
@@ -232,24 +275,18 @@ pub(super) fn codegen_simd_intrinsic_call<'tcx>(
             let base = codegen_operand(fx, base);
             let val = codegen_operand(fx, val);
 
-            // FIXME validate
-            let idx_const = if let Some(idx_const) =
-                crate::constant::mir_operand_get_const_val(fx, idx)
-            {
-                idx_const
-            } else {
-                fx.tcx.sess.span_fatal(span, "Index argument for `simd_insert` is not a constant");
-            };
+            if !base.layout().ty.is_simd() {
+                report_simd_type_validation_error(fx, intrinsic, span, base.layout().ty);
+                return;
+            }
 
-            let idx = idx_const
-                .try_to_bits(Size::from_bytes(4 /* u32*/))
-                .unwrap_or_else(|| panic!("kind not scalar: {:?}", idx_const));
+            let Some(idx) = simd_const_lane_index(fx, intrinsic, span, idx) else {
+                return;
+            };
             let (lane_count, _lane_ty) = base.layout().ty.simd_size_and_type(fx.tcx);
             if idx >= lane_count.into() {
-                fx.tcx.sess.span_fatal(
-                    fx.mir.span,
-                    format!("[simd_insert] idx {} >= lane_count {}", idx, lane_count),
-                );
+                report_simd_lane_index_out_of_bounds(fx, intrinsic, span, idx, lane_count);
+                return;
             }
 
             ret.write_cvalue(fx, base);
@@ -271,33 +308,13 @@ pub(super) fn codegen_simd_intrinsic_call<'tcx>(
                 return;
             }
 
-            let idx_const = if let Some(idx_const) =
-                crate::constant::mir_operand_get_const_val(fx, idx)
-            {
-                idx_const
-            } else {
-                fx.tcx.sess.span_warn(span, "Index argument for `simd_extract` is not a constant");
-                let trap_block = fx.bcx.create_block();
-                let true_ = fx.bcx.ins().iconst(types::I8, 1);
-                let ret_block = fx.get_block(target);
-                fx.bcx.ins().brif(true_, trap_block, &[], ret_block, &[]);
-                fx.bcx.switch_to_block(trap_block);
-                crate::trap::trap_unimplemented(
-                    fx,
-                    "Index argument for `simd_extract` is not a constant",
-                );
+            let Some(idx) = simd_const_lane_index(fx, intrinsic, span, idx) else {
                 return;
             };
-
-            let idx = idx_const
-                .try_to_bits(Size::from_bytes(4 /* u32*/))
-                .unwrap_or_else(|| panic!("kind not scalar: {:?}", idx_const));
             let (lane_count, _lane_ty) = v.layout().ty.simd_size_and_type(fx.tcx);
             if idx >= lane_count.into() {
-                fx.tcx.sess.span_fatal(
-                    fx.mir.span,
-                    format!("[simd_extract] idx {} >= lane_count {}", idx, lane_count),
-                );
+                report_simd_lane_index_out_of_bounds(fx, intrinsic, span, idx, lane_count);
+                return;
             }
 
             let ret_lane = v.value_lane(fx, idx.try_into().unwrap());
@@ -336,53 +353,72 @@ pub(super) fn codegen_simd_intrinsic_call<'tcx>(
         | sym::simd_xor => {
             intrinsic_args!(fx, args => (x, y); intrinsic);
 
-            // FIXME use vector instructions when possible
-            simd_pair_for_each_lane(fx, x, y, ret, &|fx, lane_ty, _ret_lane_ty, x_lane, y_lane| {
-                match (lane_ty.kind(), intrinsic) {
-                    (ty::Uint(_), sym::simd_add) => fx.bcx.ins().iadd(x_lane, y_lane),
-                    (ty::Uint(_), sym::simd_sub) => fx.bcx.ins().isub(x_lane, y_lane),
-                    (ty::Uint(_), sym::simd_mul) => fx.bcx.ins().imul(x_lane, y_lane),
-                    (ty::Uint(_), sym::simd_div) => fx.bcx.ins().udiv(x_lane, y_lane),
-                    (ty::Uint(_), sym::simd_rem) => fx.bcx.ins().urem(x_lane, y_lane),
-
-                    (ty::Int(_), sym::simd_add) => fx.bcx.ins().iadd(x_lane, y_lane),
-                    (ty::Int(_), sym::simd_sub) => fx.bcx.ins().isub(x_lane, y_lane),
-                    (ty::Int(_), sym::simd_mul) => fx.bcx.ins().imul(x_lane, y_lane),
-                    (ty::Int(_), sym::simd_div) => fx.bcx.ins().sdiv(x_lane, y_lane),
-                    (ty::Int(_), sym::simd_rem) => fx.bcx.ins().srem(x_lane, y_lane),
-
-                    (ty::Float(_), sym::simd_add) => fx.bcx.ins().fadd(x_lane, y_lane),
-                    (ty::Float(_), sym::simd_sub) => fx.bcx.ins().fsub(x_lane, y_lane),
-                    (ty::Float(_), sym::simd_mul) => fx.bcx.ins().fmul(x_lane, y_lane),
-                    (ty::Float(_), sym::simd_div) => fx.bcx.ins().fdiv(x_lane, y_lane),
-                    (ty::Float(FloatTy::F32), sym::simd_rem) => fx.lib_call(
-                        "fmodf",
-                        vec![AbiParam::new(types::F32), AbiParam::new(types::F32)],
-                        vec![AbiParam::new(types::F32)],
-                        &[x_lane, y_lane],
-                    )[0],
-                    (ty::Float(FloatTy::F64), sym::simd_rem) => fx.lib_call(
-                        "fmod",
-                        vec![AbiParam::new(types::F64), AbiParam::new(types::F64)],
-                        vec![AbiParam::new(types::F64)],
-                        &[x_lane, y_lane],
-                    )[0],
+            if !x.layout().ty.is_simd() {
+                report_simd_type_validation_error(fx, intrinsic, span, x.layout().ty);
+                return;
+            }
 
-                    (ty::Uint(_), sym::simd_shl) => fx.bcx.ins().ishl(x_lane, y_lane),
-                    (ty::Uint(_), sym::simd_shr) => fx.bcx.ins().ushr(x_lane, y_lane),
-                    (ty::Uint(_), sym::simd_and) => fx.bcx.ins().band(x_lane, y_lane),
-                    (ty::Uint(_), sym::simd_or) => fx.bcx.ins().bor(x_lane, y_lane),
-                    (ty::Uint(_), sym::simd_xor) => fx.bcx.ins().bxor(x_lane, y_lane),
+            let lane_ty = x.layout().ty.simd_size_and_type(fx.tcx).1;
+            let op = match (lane_ty.kind(), intrinsic) {
+                (ty::Uint(_), sym::simd_add) => Some(Opcode::Iadd),
+                (ty::Uint(_), sym::simd_sub) => Some(Opcode::Isub),
+                (ty::Uint(_), sym::simd_mul) => Some(Opcode::Imul),
+                (ty::Uint(_), sym::simd_div) => Some(Opcode::Udiv),
+                (ty::Uint(_), sym::simd_rem) => Some(Opcode::Urem),
+
+                (ty::Int(_), sym::simd_add) => Some(Opcode::Iadd),
+                (ty::Int(_), sym::simd_sub) => Some(Opcode::Isub),
+                (ty::Int(_), sym::simd_mul) => Some(Opcode::Imul),
+                (ty::Int(_), sym::simd_div) => Some(Opcode::Sdiv),
+                (ty::Int(_), sym::simd_rem) => Some(Opcode::Srem),
+
+                (ty::Float(_), sym::simd_add) => Some(Opcode::Fadd),
+                (ty::Float(_), sym::simd_sub) => Some(Opcode::Fsub),
+                (ty::Float(_), sym::simd_mul) => Some(Opcode::Fmul),
+                (ty::Float(_), sym::simd_div) => Some(Opcode::Fdiv),
+                (ty::Float(_), sym::simd_rem) => None,
+
+                (ty::Uint(_), sym::simd_shl) => Some(Opcode::Ishl),
+                (ty::Uint(_), sym::simd_shr) => Some(Opcode::Ushr),
+                (ty::Uint(_), sym::simd_and) => Some(Opcode::Band),
+                (ty::Uint(_), sym::simd_or) => Some(Opcode::Bor),
+                (ty::Uint(_), sym::simd_xor) => Some(Opcode::Bxor),
+
+                (ty::Int(_), sym::simd_shl) => Some(Opcode::Ishl),
+                (ty::Int(_), sym::simd_shr) => Some(Opcode::Sshr),
+                (ty::Int(_), sym::simd_and) => Some(Opcode::Band),
+                (ty::Int(_), sym::simd_or) => Some(Opcode::Bor),
+                (ty::Int(_), sym::simd_xor) => Some(Opcode::Bxor),
 
-                    (ty::Int(_), sym::simd_shl) => fx.bcx.ins().ishl(x_lane, y_lane),
-                    (ty::Int(_), sym::simd_shr) => fx.bcx.ins().sshr(x_lane, y_lane),
-                    (ty::Int(_), sym::simd_and) => fx.bcx.ins().band(x_lane, y_lane),
-                    (ty::Int(_), sym::simd_or) => fx.bcx.ins().bor(x_lane, y_lane),
-                    (ty::Int(_), sym::simd_xor) => fx.bcx.ins().bxor(x_lane, y_lane),
+                _ => unreachable!(),
+            };
 
-                    _ => unreachable!(),
-                }
-            });
+            if let Some(op) = op {
+                simd_lanewise_binop(fx, x, y, ret, op);
+            } else {
+                // Cranelift doesn't have a float remainder instruction
+                simd_pair_for_each_lane(
+                    fx,
+                    x,
+                    y,
+                    ret,
+                    &|fx, lane_ty, _ret_lane_ty, x_lane, y_lane| match lane_ty.kind() {
+                        ty::Float(FloatTy::F32) => fx.lib_call(
+                            "fmodf",
+                            vec![AbiParam::new(types::F32), AbiParam::new(types::F32)],
+                            vec![AbiParam::new(types::F32)],
+                            &[x_lane, y_lane],
+                        )[0],
+                        ty::Float(FloatTy::F64) => fx.lib_call(
+                            "fmod",
+                            vec![AbiParam::new(types::F64), AbiParam::new(types::F64)],
+                            vec![AbiParam::new(types::F64)],
+                            &[x_lane, y_lane],
+                        )[0],
+                        _ => unreachable!(),
+                    },
+                );
+            }
         }
 
         sym::simd_fma => {
@@ -534,7 +570,7 @@ pub(super) fn codegen_simd_intrinsic_call<'tcx>(
                 return;
             }
 
-            simd_reduce_bool(fx, v, ret, &|fx, a, b| fx.bcx.ins().band(a, b));
+            simd_reduce_bool(fx, v, ret, true);
         }
 
         sym::simd_reduce_any => {
@@ -545,7 +581,7 @@ pub(super) fn codegen_simd_intrinsic_call<'tcx>(
                 return;
             }
 
-            simd_reduce_bool(fx, v, ret, &|fx, a, b| fx.bcx.ins().bor(a, b));
+            simd_reduce_bool(fx, v, ret, false);
         }
 
         sym::simd_reduce_and => {
@@ -769,7 +805,24 @@ pub(super) fn codegen_simd_intrinsic_call<'tcx>(
             });
         }
 
-        sym::simd_expose_addr | sym::simd_from_exposed_addr | sym::simd_cast_ptr => {
+        sym::simd_expose_addr | sym::simd_from_exposed_addr => {
+            intrinsic_args!(fx, args => (arg); intrinsic);
+
+            if !arg.layout().ty.is_simd() {
+                report_simd_type_validation_error(fx, intrinsic, span, arg.layout().ty);
+                return;
+            }
+
+            // Both pointers and their addresses are represented as integers of the target pointer
+            // width, so every lane only needs to be converted to the lane type of the result.
+            simd_for_each_lane(fx, arg, ret, &|fx, _lane_ty, ret_lane_ty, lane| {
+                let ret_lane_clif_ty = fx.clif_type(ret_lane_ty).unwrap();
+                assert_eq!(ret_lane_clif_ty, fx.pointer_type);
+                clif_intcast(fx, lane, ret_lane_clif_ty, false)
+            });
+        }
+
+        sym::simd_cast_ptr => {
             intrinsic_args!(fx, args => (arg); intrinsic);
             ret.write_cvalue_transmute(fx, arg);
         }
@@ -128,6 +128,7 @@ struct CodegenCx {
     profiler: SelfProfilerRef,
     output_filenames: Arc<OutputFilenames>,
     should_write_ir: bool,
+    dump_fn: Option<String>,
     global_asm: String,
     inline_asm_index: Cell<usize>,
     debug_context: Option<DebugContext>,
@@ -156,6 +157,7 @@ fn new(
             profiler: tcx.prof.clone(),
             output_filenames: tcx.output_filenames(()).clone(),
             should_write_ir: crate::pretty_clif::should_write_ir(tcx),
+            dump_fn: backend_config.dump_fn,
             global_asm: String::new(),
             inline_asm_index: Cell::new(0),
             debug_context,
@@ -259,6 +259,17 @@ pub(crate) fn write_clif_file(
     });
 }
 
+/// Print the clif ir of a single function to stderr. Used for `CG_CLIF_DUMP_FN`.
+pub(crate) fn print_clif(
+    symbol_name: &str,
+    func: &cranelift_codegen::ir::Function,
+    mut clif_comments: &CommentWriter,
+) {
+    let mut clif = String::new();
+    cranelift_codegen::write::decorate_function(&mut clif_comments, &mut clif, func).unwrap();
+    eprintln!("; clif ir of {}\n{}", symbol_name, clif);
+}
+
 impl fmt::Debug for FunctionCx<'_, '_, '_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{:?}", self.instance.substs)?;
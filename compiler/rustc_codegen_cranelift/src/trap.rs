@@ -2,11 +2,12 @@
 
 use crate::prelude::*;
 
-fn codegen_print(fx: &mut FunctionCx<'_, '_, '_>, msg: &str) {
-    let puts = fx
+/// Call a libc function taking a single pointer and returning an `int`.
+fn codegen_libc_call(fx: &mut FunctionCx<'_, '_, '_>, name: &str, arg: Value) {
+    let func_id = fx
         .module
         .declare_function(
-            "puts",
+            name,
             Linkage::Import,
             &Signature {
                 call_conv: fx.target_config.default_call_conv,
@@ -15,20 +16,39 @@ fn codegen_print(fx: &mut FunctionCx<'_, '_, '_>, msg: &str) {
             },
         )
         .unwrap();
-    let puts = fx.module.declare_func_in_func(puts, &mut fx.bcx.func);
+    let func_ref = fx.module.declare_func_in_func(func_id, &mut fx.bcx.func);
     if fx.clif_comments.enabled() {
-        fx.add_comment(puts, "puts");
+        fx.add_comment(func_ref, name);
     }
+    fx.bcx.ins().call(func_ref, &[arg]);
+}
 
+fn codegen_print(fx: &mut FunctionCx<'_, '_, '_>, msg: &str) {
     let real_msg = format!("trap at {:?} ({}): {}\0", fx.instance, fx.symbol_name, msg);
     let msg_ptr = fx.anonymous_str(&real_msg);
-    fx.bcx.ins().call(puts, &[msg_ptr]);
+    codegen_libc_call(fx, "puts", msg_ptr);
+
+    // stdout is fully buffered when it isn't a terminal and the trap that follows kills the
+    // process without flushing it, which would lose the message when the output is redirected.
+    // Passing a null pointer to fflush flushes all output streams.
+    let all_streams = fx.bcx.ins().iconst(fx.pointer_type, 0);
+    codegen_libc_call(fx, "fflush", all_streams);
 }
 
 /// Use this when something is unimplemented, but `libcore` or `libstd` requires it to codegen.
+/// When reached at runtime the function and `msg` are printed to stdout before trapping.
 ///
 /// Trap code: user65535
 pub(crate) fn trap_unimplemented(fx: &mut FunctionCx<'_, '_, '_>, msg: impl AsRef<str>) {
     codegen_print(fx, msg.as_ref());
     fx.bcx.ins().trap(TrapCode::User(!0));
 }
+
+/// Like `trap_unimplemented`, but for calls to an intrinsic that isn't supported. The printed
+/// message names the intrinsic so that the abort can be traced back to it.
+///
+/// Trap code: user65534
+pub(crate) fn trap_unsupported_intrinsic(fx: &mut FunctionCx<'_, '_, '_>, intrinsic: &str) {
+    codegen_print(fx, &format!("unsupported intrinsic `{intrinsic}`"));
+    fx.bcx.ins().trap(TrapCode::User(!1));
+}
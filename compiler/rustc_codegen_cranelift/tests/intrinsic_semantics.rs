@@ -0,0 +1,246 @@
+// Compare a selection of the x86 intrinsics emulated by cg_clif against a scalar model of their
+// semantics on random inputs. This catches lane ordering and sign handling bugs which are easy to
+// miss with a few handpicked inputs. Intrinsics whose target feature isn't available on the host
+// are skipped.
+//
+// This is built with cg_clif by `./y.rs test`. `cargo test` builds it with LLVM instead, which
+// checks the scalar models themselves.
+
+fn main() {
+    #[cfg(target_arch = "x86_64")]
+    x86_64::run();
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86_64 {
+    use std::arch::x86_64::*;
+    use std::mem::transmute;
+
+    const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+    const ITERATIONS: usize = 256;
+
+    /// xorshift64* to keep the inputs reproducible without depending on the rand crate.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn bytes<const N: usize>(&mut self) -> [u8; N] {
+            let mut res = [0; N];
+            for byte in &mut res {
+                *byte = self.next_u64() as u8;
+            }
+            res
+        }
+
+        fn f32s<const N: usize>(&mut self) -> [f32; N] {
+            const SPECIAL: [f32; 6] = [0.0, -0.0, 1.0, f32::NAN, f32::INFINITY, f32::NEG_INFINITY];
+            let mut res = [0.0; N];
+            for lane in &mut res {
+                let r = self.next_u64();
+                // Pick special values often enough that equal and unordered lanes actually occur
+                *lane = if r % 4 == 0 {
+                    SPECIAL[(r >> 8) as usize % SPECIAL.len()]
+                } else {
+                    (r >> 32) as i32 as f32 / 65536.0
+                };
+            }
+            res
+        }
+
+        fn f64s<const N: usize>(&mut self) -> [f64; N] {
+            let mut res = [0.0; N];
+            for (lane, f) in res.iter_mut().zip(self.f32s::<N>()) {
+                *lane = f64::from(f);
+            }
+            res
+        }
+    }
+
+    fn movemask_model<const N: usize>(lanes: [u64; N], lane_bits: u32) -> i32 {
+        let mut res = 0;
+        for (i, lane) in lanes.into_iter().enumerate() {
+            res |= ((lane >> (lane_bits - 1)) as i32 & 1) << i;
+        }
+        res
+    }
+
+    /// The comparison model for the predicate immediates of `_mm_cmp_p{s,d}`.
+    fn cmp_model(kind: i32, a: f64, b: f64) -> bool {
+        let unordered = a.is_nan() || b.is_nan();
+        match kind {
+            0 => a == b,
+            1 => a < b,
+            2 => a <= b,
+            3 => unordered,
+            4 => a != b,
+            5 => unordered || a >= b,
+            6 => unordered || a > b,
+            7 => !unordered,
+            _ => unreachable!(),
+        }
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn check_mm_movemask_epi8(rng: &mut Rng) {
+        let a = rng.bytes::<16>();
+        let r = _mm_movemask_epi8(transmute(a));
+        assert_eq!(r, movemask_model(a.map(u64::from), 8), "a={a:?}");
+    }
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn check_mm256_movemask_epi8(rng: &mut Rng) {
+        let a = rng.bytes::<32>();
+        let r = _mm256_movemask_epi8(transmute(a));
+        assert_eq!(r, movemask_model(a.map(u64::from), 8), "a={a:?}");
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn check_mm_movemask_pd(rng: &mut Rng) {
+        let a = rng.f64s::<2>();
+        let r = _mm_movemask_pd(transmute(a));
+        assert_eq!(r, movemask_model(a.map(f64::to_bits), 64), "a={a:?}");
+    }
+
+    #[target_feature(enable = "sse")]
+    unsafe fn check_mm_cmp_ps(rng: &mut Rng) {
+        let a = rng.f32s::<4>();
+        let b = rng.f32s::<4>();
+        let (va, vb): (__m128, __m128) = (transmute(a), transmute(b));
+        let results: [(i32, __m128); 8] = [
+            (0, _mm_cmpeq_ps(va, vb)),
+            (1, _mm_cmplt_ps(va, vb)),
+            (2, _mm_cmple_ps(va, vb)),
+            (3, _mm_cmpunord_ps(va, vb)),
+            (4, _mm_cmpneq_ps(va, vb)),
+            (5, _mm_cmpnlt_ps(va, vb)),
+            (6, _mm_cmpnle_ps(va, vb)),
+            (7, _mm_cmpord_ps(va, vb)),
+        ];
+        for (kind, r) in results {
+            let r = transmute::<_, [u32; 4]>(r);
+            for lane in 0..4 {
+                let expected = cmp_model(kind, a[lane].into(), b[lane].into());
+                let expected = if expected { u32::MAX } else { 0 };
+                assert_eq!(r[lane], expected, "kind={kind} lane={lane} a={a:?} b={b:?}");
+            }
+        }
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn check_mm_cmp_pd(rng: &mut Rng) {
+        let a = rng.f64s::<2>();
+        let b = rng.f64s::<2>();
+        let (va, vb): (__m128d, __m128d) = (transmute(a), transmute(b));
+        let results: [(i32, __m128d); 4] = [
+            (0, _mm_cmpeq_pd(va, vb)),
+            (1, _mm_cmplt_pd(va, vb)),
+            (3, _mm_cmpunord_pd(va, vb)),
+            (5, _mm_cmpnlt_pd(va, vb)),
+        ];
+        for (kind, r) in results {
+            let r = transmute::<_, [u64; 2]>(r);
+            for lane in 0..2 {
+                let expected = cmp_model(kind, a[lane], b[lane]);
+                let expected = if expected { u64::MAX } else { 0 };
+                assert_eq!(r[lane], expected, "kind={kind} lane={lane} a={a:?} b={b:?}");
+            }
+        }
+    }
+
+    #[target_feature(enable = "sse")]
+    unsafe fn check_mm_cmp_ss(rng: &mut Rng) {
+        let a = rng.f32s::<4>();
+        let b = rng.f32s::<4>();
+        let r = transmute::<_, [u32; 4]>(_mm_cmplt_ss(transmute(a), transmute(b)));
+        let expected = if cmp_model(1, a[0].into(), b[0].into()) { u32::MAX } else { 0 };
+        assert_eq!(r[0], expected, "a={a:?} b={b:?}");
+        assert_eq!(r[1..], a.map(f32::to_bits)[1..], "a={a:?} b={b:?}");
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn check_mm_mul_epu32(rng: &mut Rng) {
+        let a = transmute::<_, [u32; 4]>(rng.bytes::<16>());
+        let b = transmute::<_, [u32; 4]>(rng.bytes::<16>());
+        let r = transmute::<_, [u64; 2]>(_mm_mul_epu32(transmute(a), transmute(b)));
+        let expected = [0, 2].map(|i| u64::from(a[i]) * u64::from(b[i]));
+        assert_eq!(r, expected, "a={a:?} b={b:?}");
+    }
+
+    #[target_feature(enable = "sse4.1")]
+    unsafe fn check_mm_mul_epi32(rng: &mut Rng) {
+        let a = transmute::<_, [i32; 4]>(rng.bytes::<16>());
+        let b = transmute::<_, [i32; 4]>(rng.bytes::<16>());
+        let r = transmute::<_, [i64; 2]>(_mm_mul_epi32(transmute(a), transmute(b)));
+        let expected = [0, 2].map(|i| i64::from(a[i]) * i64::from(b[i]));
+        assert_eq!(r, expected, "a={a:?} b={b:?}");
+    }
+
+    struct Case {
+        name: &'static str,
+        is_supported: fn() -> bool,
+        check: unsafe fn(&mut Rng),
+    }
+
+    const CASES: &[Case] = &[
+        Case {
+            name: "_mm_movemask_epi8",
+            is_supported: || is_x86_feature_detected!("sse2"),
+            check: check_mm_movemask_epi8,
+        },
+        Case {
+            name: "_mm256_movemask_epi8",
+            is_supported: || is_x86_feature_detected!("avx2"),
+            check: check_mm256_movemask_epi8,
+        },
+        Case {
+            name: "_mm_movemask_pd",
+            is_supported: || is_x86_feature_detected!("sse2"),
+            check: check_mm_movemask_pd,
+        },
+        Case {
+            name: "_mm_cmp*_ps",
+            is_supported: || is_x86_feature_detected!("sse"),
+            check: check_mm_cmp_ps,
+        },
+        Case {
+            name: "_mm_cmp*_pd",
+            is_supported: || is_x86_feature_detected!("sse2"),
+            check: check_mm_cmp_pd,
+        },
+        Case {
+            name: "_mm_cmplt_ss",
+            is_supported: || is_x86_feature_detected!("sse"),
+            check: check_mm_cmp_ss,
+        },
+        Case {
+            name: "_mm_mul_epu32",
+            is_supported: || is_x86_feature_detected!("sse2"),
+            check: check_mm_mul_epu32,
+        },
+        Case {
+            name: "_mm_mul_epi32",
+            is_supported: || is_x86_feature_detected!("sse4.1"),
+            check: check_mm_mul_epi32,
+        },
+    ];
+
+    pub(crate) fn run() {
+        for case in CASES {
+            if !(case.is_supported)() {
+                println!("skipping {}: target feature not detected", case.name);
+                continue;
+            }
+
+            let mut rng = Rng(SEED);
+            for _ in 0..ITERATIONS {
+                unsafe { (case.check)(&mut rng) };
+            }
+        }
+    }
+}